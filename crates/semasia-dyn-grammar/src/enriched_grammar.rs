@@ -2,15 +2,86 @@ use crate::{
     conflicts::{Associativity, ProductionPriority, TokenPriority},
     grammar::{Grammar, NonTerminal, Production, Symbol, Token},
 };
+use itertools::Itertools;
 use syn::Ident;
 
-pub type EnrichedToken = Token<Ident, (Vec<syn::Attribute>, TokenPriority, Associativity)>;
+pub type EnrichedToken = Token<
+    Ident,
+    (
+        Vec<syn::Attribute>,
+        TokenPriority,
+        Associativity,
+        String,
+        TokenValue,
+    ),
+>;
+
+/// How a token gets its value, two tokens lexed the same way can only be merged when they get it
+/// the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenValue {
+    /// A unit struct, which is built by the lexer.
+    Unit,
+    /// A type alias, with the aliased type.
+    Alias(String),
+    /// Any other declaration, which is a type of its own.
+    Own,
+}
 
 pub type EnrichedNonTerminal = NonTerminal<Ident>;
 
 pub type EnrichedSymbol = Symbol<EnrichedToken, EnrichedNonTerminal>;
 
-pub struct Context(pub Option<Ident>);
+pub struct Context {
+    pub compiler_ctx: Option<Ident>,
+    pub sub_grammars: Vec<SubGrammar>,
+    pub generic: Option<Generic>,
+    /// The `#[predicate]` of each production that has one.
+    pub predicates: Vec<(Ident, syn::ExprClosure)>,
+    /// The `#[reclassify]` of the compilation context, run on every token before it's parsed and
+    /// again after each reduction the token triggers.
+    pub reclassify: Option<syn::ExprClosure>,
+    /// Body elements lexed as a unit token declared by another grammar: the production, the
+    /// position in its body and the unit struct it expects there.
+    pub unit_conversions: Vec<(Ident, usize, Ident)>,
+}
+
+/// Symbols merged from a nested `#[grammar]` module, `path` is relative to the grammar that
+/// contains it. Symbols, productions and the context are named as in the module, in the merged
+/// grammar they get the name given by `local_name`.
+pub struct SubGrammar {
+    pub path: Vec<Ident>,
+    pub symbols: Vec<Ident>,
+    pub compiler_ctx: Option<Ident>,
+    pub productions: Vec<Ident>,
+}
+
+impl SubGrammar {
+    /// The name of an item of the module in the merged grammar: `negations__OpenPar` for
+    /// `negations::OpenPar`.
+    pub fn local_name(&self, item: &Ident) -> Ident {
+        let path = self.path.iter().map(ToString::to_string).join("__");
+        Ident::new(&format!("{path}__{item}"), item.span())
+    }
+
+    /// The items of the module with their names in the merged grammar.
+    pub fn items(&self) -> impl Iterator<Item = (Ident, &Ident)> {
+        self.symbols
+            .iter()
+            .chain(&self.productions)
+            .chain(&self.compiler_ctx)
+            .map(|item| (self.local_name(item), item))
+    }
+}
+
+/// The type parameter of a generic grammar, which is generated once for each of its instances.
+pub struct Generic {
+    pub parameter: Ident,
+    pub symbols: Vec<Ident>,
+    pub instances: Vec<syn::Type>,
+    /// The `#[token]` and `#[regex]` attributes of each instance, for the tokens that have them.
+    pub token_attributes: Vec<(Ident, Vec<Vec<syn::Attribute>>)>,
+}
 
 pub type EnrichedBaseProduction = Production<Ident, Ident, Ident, Option<usize>>;
 
@@ -287,6 +287,47 @@ impl<ProductionId, HeadType, BodySymbol, Extras>
     pub fn extras(&self) -> &Extras {
         &self.extras
     }
+
+    pub fn map<OtherProductionId, OtherHeadType, OtherBodySymbol, OtherExtras, F>(
+        self,
+        mapper: F,
+    ) -> Production<OtherProductionId, OtherHeadType, OtherBodySymbol, OtherExtras>
+    where
+        F: FnOnce(
+            ProductionId,
+            HeadType,
+            Body<BodySymbol>,
+            Extras,
+        ) -> (
+            OtherProductionId,
+            OtherHeadType,
+            Body<OtherBodySymbol>,
+            OtherExtras,
+        ),
+    {
+        let (id, head, body, extras) = mapper(self.id, self.head, self.body, self.extras);
+        Production {
+            id,
+            head,
+            body,
+            extras,
+        }
+    }
+
+    pub fn map_body<OtherBodySymbol, F>(
+        self,
+        mapper: F,
+    ) -> Production<ProductionId, HeadType, OtherBodySymbol, Extras>
+    where
+        F: FnMut(BodySymbol) -> OtherBodySymbol,
+    {
+        Production {
+            id: self.id,
+            head: self.head,
+            body: self.body.into_iter().map(mapper).collect(),
+            extras: self.extras,
+        }
+    }
 }
 
 impl EnrichedBaseProduction {
@@ -341,6 +382,7 @@ pub struct Grammar<TokenType, NonTerminalType, ProductionType, Extras = ()> {
     tokens: Vec<TokenType>,
     non_terminals: Vec<NonTerminalType>,
     start_symbol: usize,
+    entry_points: Vec<usize>,
     productions: Vec<ProductionType>,
     extras: Extras,
 }
@@ -352,6 +394,7 @@ impl<TokenType, NonTerminalType, ProductionType, Extras>
         tokens: Vec<TokenType>,
         non_terminals: Vec<NonTerminalType>,
         start_symbol: usize,
+        entry_points: Vec<usize>,
         productions: Vec<ProductionType>,
         extras: Extras,
     ) -> Self {
@@ -359,6 +402,7 @@ impl<TokenType, NonTerminalType, ProductionType, Extras>
             tokens,
             non_terminals,
             start_symbol,
+            entry_points,
             productions,
             extras,
         }
@@ -386,6 +430,13 @@ impl<TokenType, NonTerminalType, ProductionType, Extras>
             .unwrap_or_else(|| abort_call_site!("every grammar has to have a start symbol"))
     }
 
+    /// Non-terminals other than the start symbol that can be parsed on their own.
+    pub fn entry_points(&self) -> impl Iterator<Item = &NonTerminalType> {
+        self.entry_points
+            .iter()
+            .map(|&entry_point| &self.non_terminals()[entry_point])
+    }
+
     pub fn productions(&self) -> &Vec<ProductionType> {
         &self.productions
     }
@@ -397,6 +448,10 @@ impl<TokenType, NonTerminalType, ProductionType, Extras>
     pub fn extras(&self) -> &Extras {
         &self.extras
     }
+
+    pub fn into_productions(self) -> Vec<ProductionType> {
+        self.productions
+    }
 }
 
 impl From<EnrichedGrammar> for SymbolicGrammar {
@@ -413,29 +468,28 @@ impl From<EnrichedGrammar> for SymbolicGrammar {
             .enumerate()
             .map(|(id, enr)| SymbolicNonTerminal::new(id, enr))
             .collect_vec();
-        let extra_production = SymbolicProduction::new(
-            value.productions.len(),
-            SymbolicNonTerminal::new(
-                non_terminals.len(),
-                EnrichedNonTerminal::new(
-                    Ident::new(
-                        "__SemasiaStart",
-                        non_terminals[value.start_symbol].extras().id().span(),
+        // one augmented production per entry point, the start symbol's comes first
+        let extra_productions = iter::once(value.start_symbol)
+            .chain(value.entry_points.iter().copied())
+            .enumerate()
+            .map(|(i, entry_point)| {
+                let span = non_terminals[entry_point].extras().id().span();
+                SymbolicProduction::new(
+                    value.productions.len() + i,
+                    SymbolicNonTerminal::new(
+                        non_terminals.len(),
+                        EnrichedNonTerminal::new(Ident::new("__SemasiaStart", span), ()),
+                    ),
+                    Body::new(vec![SymbolicSymbol::NonTerminal(
+                        non_terminals[entry_point].clone(),
+                    )]),
+                    (
+                        Ident::new("__SemasiaParse", span),
+                        ProductionPriority::Explicit(usize::MAX),
                     ),
-                    (),
-                ),
-            ),
-            Body::new(vec![SymbolicSymbol::NonTerminal(
-                non_terminals[value.start_symbol].clone(),
-            )]),
-            (
-                Ident::new(
-                    "__SemasiaParse",
-                    non_terminals[value.start_symbol].extras().id().span(),
-                ),
-                ProductionPriority::Explicit(usize::MAX),
-            ),
-        );
+                )
+            })
+            .collect_vec();
         let productions = value
             .productions
             .into_iter()
@@ -478,12 +532,13 @@ impl From<EnrichedGrammar> for SymbolicGrammar {
                     (enr.id, enr.extras),
                 )
             })
-            .chain(iter::once(extra_production))
+            .chain(extra_productions)
             .collect_vec();
         SymbolicGrammar::new(
             tokens,
             non_terminals,
             value.start_symbol,
+            value.entry_points,
             productions,
             value.extras,
         )
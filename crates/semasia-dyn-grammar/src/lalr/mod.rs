@@ -6,11 +6,15 @@ use crate::{
         action::{EofAction, TokenAction},
         tables::{EofTable, NonTerminalTable, TokenTable, TransitionTables},
     },
-    symbolic_grammar::{SymbolicGrammar, SymbolicProduction, SymbolicSymbol, SymbolicToken},
+    symbolic_grammar::{
+        SymbolicGrammar, SymbolicNonTerminal, SymbolicProduction, SymbolicSymbol, SymbolicToken,
+    },
 };
 use itertools::Itertools;
 use proc_macro_error::{abort_if_dirty, emit_error};
-use std::{cell::RefCell, cmp::Ordering, collections::HashSet, fmt::Display, hash::Hash, rc::Rc};
+use std::{
+    cell::RefCell, cmp::Ordering, collections::HashSet, fmt::Display, hash::Hash, iter, rc::Rc,
+};
 
 #[derive(Clone)]
 struct LookAhead<'a> {
@@ -210,7 +214,12 @@ impl<'a> LalrState<'a> {
         }
     }
 
-    fn closure(&self, counter: &mut usize, grammar: &'a SymbolicGrammar) -> HashSet<LalrItem<'a>> {
+    fn closure(
+        &self,
+        counter: &mut usize,
+        grammar: &'a SymbolicGrammar,
+        nullable: &HashSet<usize>,
+    ) -> HashSet<LalrItem<'a>> {
         let mut stack = self.kernel.clone().into_iter().collect_vec();
         let mut res = self.kernel.clone();
 
@@ -228,7 +237,7 @@ impl<'a> LalrState<'a> {
 
             let beta = &item_production.body()[item.marker_position + 1..];
 
-            let firsts = grammar.first_set(beta);
+            let firsts = grammar.first_set(beta, nullable);
             let natural_lookahead = LookAhead {
                 tokens: firsts.tokens,
                 can_eof_follow: false,
@@ -280,15 +289,21 @@ impl<'a> LalrAutomaton<'a> {
 
     pub fn populate(&mut self) {
         let mut counter = 0;
-        let first_state = LalrState::new(HashSet::from_iter([LalrItem::accepting(
-            self.grammar.productions().last().unwrap(),
-            &mut counter,
-        )]));
-        self.states.push(first_state);
+        let nullable = self.grammar.nullable_non_terminals();
+        // the augmented productions come last, each of them seeds the initial state of its entry
+        // point, so the initial state of the n-th entry point is the n-th state
+        let productions = self.grammar.productions();
+        for production in &productions[productions.len() - self.entry_point_count()..] {
+            let initial_state = LalrState::new(HashSet::from_iter([LalrItem::accepting(
+                production,
+                &mut counter,
+            )]));
+            self.states.push(initial_state);
+        }
 
         while let Some(state) = self.states.iter_mut().find(|state| !state.marked) {
             state.marked = true;
-            let closure = state.closure(&mut counter, self.grammar);
+            let closure = state.closure(&mut counter, self.grammar, &nullable);
             for eps_item in closure.iter().filter(|item| {
                 self.grammar
                     .productions()
@@ -326,49 +341,52 @@ impl<'a> LalrAutomaton<'a> {
                 .collect::<Vec<_>>();
             let token_transitions = token_transitions
                 .into_iter()
-                .map(|target_state| {
-                    target_state.map(|target_state| {
-                        match self.states.iter().position(|state| state == &target_state) {
-                            Some(i) => {
-                                for new_item in target_state.kernel.iter() {
-                                    self.states[i]
-                                        .kernel
-                                        .get(new_item)
-                                        .unwrap()
-                                        .lookahead_node
-                                        .add_dependency(new_item.lookahead_node.clone());
-                                }
-                                i
-                            }
-                            None => {
-                                let state_id = self.states.len();
-                                self.states.push(target_state);
-                                state_id
-                            }
-                        }
-                    })
-                })
+                .map(|target_state| target_state.map(|target_state| self.add_state(target_state)))
                 .collect::<Vec<_>>();
             let non_terminal_transitions = non_terminal_transitions
                 .into_iter()
-                .map(|target_state| {
-                    target_state.map(|target_state| {
-                        match self.states.iter().position(|state| state == &target_state) {
-                            Some(i) => i,
-                            None => {
-                                let state_id = self.states.len();
-                                self.states.push(target_state);
-                                state_id
-                            }
-                        }
-                    })
-                })
+                .map(|target_state| target_state.map(|target_state| self.add_state(target_state)))
                 .collect::<Vec<_>>();
             self.transitions
                 .add_transitions(token_transitions, non_terminal_transitions);
         }
     }
 
+    /// Returns the state with the same kernel as `target_state`, whose items then also follow
+    /// the lookaheads of the new ones, or adds it as a new state.
+    fn add_state(&mut self, target_state: LalrState<'a>) -> usize {
+        match self.states.iter().position(|state| state == &target_state) {
+            Some(i) => {
+                for new_item in target_state.kernel.iter() {
+                    self.states[i]
+                        .kernel
+                        .get(new_item)
+                        .unwrap()
+                        .lookahead_node
+                        .add_dependency(new_item.lookahead_node.clone());
+                }
+                i
+            }
+            None => {
+                let state_id = self.states.len();
+                self.states.push(target_state);
+                state_id
+            }
+        }
+    }
+
+    fn entry_point_count(&self) -> usize {
+        1 + self.grammar.entry_points().count()
+    }
+
+    /// The entry points with their initial states, starting with the start symbol.
+    pub fn entry_points(&self) -> Vec<(usize, &'a SymbolicNonTerminal)> {
+        iter::once(self.grammar.start_symbol())
+            .chain(self.grammar.entry_points())
+            .enumerate()
+            .collect()
+    }
+
     pub fn states_count(&self) -> usize {
         self.states.len()
     }
@@ -377,6 +395,19 @@ impl<'a> LalrAutomaton<'a> {
         let mut token_table = TokenTable::new(self.grammar.token_count());
         let mut eof_table = EofTable::new();
         let mut goto_table = NonTerminalTable::new(self.grammar.non_terminal_count());
+        let predicated = self
+            .grammar
+            .productions()
+            .iter()
+            .filter(|production| {
+                self.grammar
+                    .extras()
+                    .predicates
+                    .iter()
+                    .any(|(name, _)| name == &production.extras().0)
+            })
+            .map(|production| *production.id())
+            .collect::<HashSet<_>>();
 
         for ((state_id, state), (token_transitions, non_terminal_transitions)) in
             self.states.iter().enumerate().zip(self.transitions.iter())
@@ -394,7 +425,19 @@ impl<'a> LalrAutomaton<'a> {
                 for token in lookahead.tokens.into_iter() {
                     let production_id = *reducing_item.production.id();
                     let mut action = TokenAction::Reduce(production_id);
-                    let entry = &mut token_table[(state_id, *token.id())];
+                    let entry = &mut token_table.table[state_id][*token.id()];
+                    let guarded = token_table
+                        .predicated
+                        .entry((state_id, *token.id()))
+                        .or_default();
+                    if Self::guard_reduction(entry, guarded, production_id, &predicated, |action| {
+                        match action {
+                            TokenAction::Reduce(reduce) => Some(*reduce),
+                            TokenAction::Shift(_) => None,
+                        }
+                    }) {
+                        continue;
+                    }
                     if let Some(TokenAction::Reduce(reduce)) = entry.take() {
                         let old_reduce = &self.grammar().productions()[reduce];
                         let new_reduce = &self.grammar().productions()[production_id];
@@ -405,7 +448,8 @@ impl<'a> LalrAutomaton<'a> {
                             Ordering::Equal => {
                                 emit_error!(
                                     old_reduce.extras().0, "reduce/reduce conflict";
-                                    note = old_reduce.extras().0.span() => "put #[priority(<value>)]"
+                                    note = old_reduce.extras().0.span() => "put #[priority(<value>)]";
+                                    help = "or choose at parse time with #[predicate(|ctx, lookahead| ...)]"
                                 );
                             }
                             Ordering::Greater => {}
@@ -420,7 +464,16 @@ impl<'a> LalrAutomaton<'a> {
                     } else {
                         EofAction::Reduce(production_id)
                     };
-                    let entry = &mut eof_table[state_id];
+                    let entry = &mut eof_table.table[state_id];
+                    let guarded = eof_table.predicated.entry(state_id).or_default();
+                    if Self::guard_reduction(entry, guarded, production_id, &predicated, |action| {
+                        match action {
+                            EofAction::Reduce(reduce) => Some(*reduce),
+                            EofAction::Accept => None,
+                        }
+                    }) {
+                        continue;
+                    }
                     if let Some(EofAction::Reduce(reduce)) = entry.take() {
                         let old_reduce = &self.grammar().productions()[reduce];
                         let new_reduce = &self.grammar().productions()[production_id];
@@ -431,7 +484,8 @@ impl<'a> LalrAutomaton<'a> {
                             Ordering::Equal => {
                                 emit_error!(
                                     old_reduce.extras().0, "reduce/reduce conflict";
-                                    note = old_reduce.extras().0.span() => "put #[priority(<value>)]"
+                                    note = old_reduce.extras().0.span() => "put #[priority(<value>)]";
+                                    help = "or choose at parse time with #[predicate(|ctx, lookahead| ...)]"
                                 );
                             }
                             Ordering::Greater => {}
@@ -447,7 +501,18 @@ impl<'a> LalrAutomaton<'a> {
                 };
                 let token = &self.grammar().tokens()[token_id];
                 let mut action = TokenAction::Shift(*target);
-                let entry = &mut token_table[(state_id, token_id)];
+                let entry = &mut token_table.table[state_id][token_id];
+                if let Some(TokenAction::Reduce(reduce)) = entry
+                    && predicated.contains(reduce)
+                {
+                    token_table
+                        .predicated
+                        .entry((state_id, token_id))
+                        .or_default()
+                        .push(*reduce);
+                    *entry = Some(action);
+                    continue;
+                }
                 if let Some(TokenAction::Reduce(reduce)) = entry.take() {
                     let reduce_production = &self.grammar().productions()[reduce];
                     let prod_priority = reduce_production.extras().1;
@@ -492,9 +557,68 @@ impl<'a> LalrAutomaton<'a> {
 
         abort_if_dirty();
 
+        for guarded in token_table
+            .predicated
+            .values_mut()
+            .chain(eof_table.predicated.values_mut())
+        {
+            guarded.sort_unstable();
+            guarded.dedup();
+        }
+        token_table
+            .predicated
+            .retain(|_, guarded| !guarded.is_empty());
+        eof_table
+            .predicated
+            .retain(|_, guarded| !guarded.is_empty());
+
         (token_table, eof_table, goto_table)
     }
 
+    /// Keeps the conflicts of productions with a predicate instead of resolving them: their
+    /// reductions are moved from `entry` to `guarded`, to be tried at parse time before the
+    /// action left in `entry`. Returns whether `production` was guarded.
+    fn guard_reduction<Action>(
+        entry: &mut Option<Action>,
+        guarded: &mut Vec<usize>,
+        production: usize,
+        predicated: &HashSet<usize>,
+        reduction: impl Fn(&Action) -> Option<usize>,
+    ) -> bool {
+        if entry.is_none() && guarded.is_empty() {
+            return false;
+        }
+        if let Some(reduce) = entry.as_ref().and_then(reduction)
+            && predicated.contains(&reduce)
+        {
+            guarded.push(reduce);
+            *entry = None;
+        }
+        if predicated.contains(&production) {
+            guarded.push(production);
+            return true;
+        }
+        false
+    }
+
+    pub fn awaited_non_terminals(&self) -> Vec<Vec<usize>> {
+        self.states
+            .iter()
+            .map(|state| {
+                state
+                    .kernel
+                    .iter()
+                    .filter_map(|item| match item.pointed_symbol(self.grammar) {
+                        Some(SymbolicSymbol::NonTerminal(non_terminal)) => Some(*non_terminal.id()),
+                        _ => None,
+                    })
+                    .sorted()
+                    .dedup()
+                    .collect()
+            })
+            .collect()
+    }
+
     pub fn grammar(&self) -> &SymbolicGrammar {
         self.grammar
     }
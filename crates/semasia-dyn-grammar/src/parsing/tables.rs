@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fmt::Display,
     ops::{Index, IndexMut},
 };
@@ -9,6 +10,8 @@ use crate::parsing::action::{EofAction, TokenAction};
 pub struct TokenTable {
     tokens_count: usize,
     pub table: Vec<Vec<Option<TokenAction>>>,
+    /// The reductions guarded by a predicate in a conflict, tried before the action in `table`.
+    pub predicated: BTreeMap<(usize, usize), Vec<usize>>,
 }
 
 impl TokenTable {
@@ -16,6 +19,7 @@ impl TokenTable {
         Self {
             tokens_count,
             table: Vec::new(),
+            predicated: BTreeMap::new(),
         }
     }
 
@@ -77,11 +81,12 @@ impl Display for TokenTable {
 #[derive(Debug, Default)]
 pub struct EofTable {
     pub table: Vec<Option<EofAction>>,
+    pub predicated: BTreeMap<usize, Vec<usize>>,
 }
 
 impl EofTable {
     pub fn new() -> Self {
-        Self { table: Vec::new() }
+        Self::default()
     }
 
     pub fn add_state(&mut self) -> usize {
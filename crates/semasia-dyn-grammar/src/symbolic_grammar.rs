@@ -27,6 +27,7 @@ impl SymbolicGrammar {
     fn first_set_helper<'a>(
         &'a self,
         beta: &'a [SymbolicSymbol],
+        nullable: &HashSet<usize>,
         visited: &mut HashSet<&'a usize>,
     ) -> FirstSet<&'a SymbolicToken> {
         if beta.is_empty() {
@@ -52,18 +53,17 @@ impl SymbolicGrammar {
                         .productions()
                         .iter()
                         .filter(|prod| prod.head() == non_terminal);
-                    let mut some_nullable = false;
                     for prod in productions.into_iter() {
-                        // eprintln!("checking {prod}");
                         if !visited.insert(prod.id()) {
                             continue;
                         }
                         let body = prod.body();
-                        let firsts = self.first_set_helper(body, visited);
+                        let firsts = self.first_set_helper(body, nullable, visited);
                         res.tokens.extend(firsts.tokens);
-                        some_nullable |= firsts.nullable;
                     }
-                    if !some_nullable {
+                    // a production that is already being visited can't tell whether its head is
+                    // nullable, as with `A -> (A, B)` and `A -> ()`
+                    if !nullable.contains(non_terminal.id()) {
                         return res;
                     }
                 }
@@ -74,7 +74,30 @@ impl SymbolicGrammar {
         res
     }
 
-    pub fn first_set<'a>(&'a self, beta: &'a [SymbolicSymbol]) -> FirstSet<&'a SymbolicToken> {
-        self.first_set_helper(beta, &mut HashSet::new())
+    /// The first set of `beta`, `nullable` are the `nullable_non_terminals` of the grammar.
+    pub fn first_set<'a>(
+        &'a self,
+        beta: &'a [SymbolicSymbol],
+        nullable: &HashSet<usize>,
+    ) -> FirstSet<&'a SymbolicToken> {
+        self.first_set_helper(beta, nullable, &mut HashSet::new())
+    }
+
+    pub fn nullable_non_terminals(&self) -> HashSet<usize> {
+        let mut nullable = HashSet::new();
+        loop {
+            let before = nullable.len();
+            for production in self.productions() {
+                if production.body().iter().all(|symbol| match symbol {
+                    Symbol::Token(_) => false,
+                    Symbol::NonTerminal(non_terminal) => nullable.contains(non_terminal.id()),
+                }) {
+                    nullable.insert(*production.head().id());
+                }
+            }
+            if nullable.len() == before {
+                return nullable;
+            }
+        }
     }
 }
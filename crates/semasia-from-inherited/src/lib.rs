@@ -1,3 +1,9 @@
+#![no_std]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
 pub struct FromInherited<Inh, Syn> {
     mapper: Box<dyn FnOnce(Inh) -> Syn>,
 }
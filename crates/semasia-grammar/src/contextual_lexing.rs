@@ -0,0 +1,295 @@
+use itertools::Itertools;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use regex_automata::nfa::thompson::pikevm::PikeVM;
+use syn::{
+    Attribute, Expr, ExprAssign, ExprLit, Ident, Lit, LitByteStr, Token, parse::ParseStream,
+    parse_quote, punctuated::Punctuated,
+};
+
+use crate::constructor::Analyzed;
+
+/// A `#[token]` or `#[regex]` pattern with the callback that makes the value of its token.
+struct Pattern {
+    kind: PatternKind,
+    callback: Option<Expr>,
+    /// The attribute without its callback, to lex this pattern alone.
+    matcher: Attribute,
+}
+
+enum PatternKind {
+    Literal(String),
+    Regex(String),
+}
+
+impl Pattern {
+    fn parse(attr: &Attribute) -> Option<Self> {
+        let literal = attr.path().is_ident("token");
+        if !literal && !attr.path().is_ident("regex") {
+            return None;
+        }
+        let mut args = attr
+            .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            .ok()?
+            .into_iter();
+        let Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(pattern),
+            ..
+        })) = args.next()
+        else {
+            return None;
+        };
+        let (callbacks, options): (Vec<_>, Vec<_>) = args.partition(|arg| match arg {
+            Expr::Assign(ExprAssign { left, .. }) => {
+                matches!(&**left, Expr::Path(path) if path.path.is_ident("callback"))
+            }
+            Expr::Path(_) | Expr::Closure(_) => true,
+            _ => false,
+        });
+        let callback = callbacks.into_iter().next().map(|callback| match callback {
+            Expr::Assign(ExprAssign { right, .. }) => *right,
+            callback => callback,
+        });
+        let path = attr.path();
+        let matcher = parse_quote!(#[#path(#pattern #(, #options)*)]);
+        let kind = if literal {
+            PatternKind::Literal(pattern.value())
+        } else {
+            PatternKind::Regex(pattern.value())
+        };
+        Some(Self {
+            kind,
+            callback,
+            matcher,
+        })
+    }
+
+    fn is_regex(&self) -> bool {
+        matches!(self.kind, PatternKind::Regex(_))
+    }
+
+    fn literal(&self) -> Option<&str> {
+        match &self.kind {
+            PatternKind::Literal(literal) => Some(literal),
+            PatternKind::Regex(_) => None,
+        }
+    }
+
+    /// Whether the whole `text` matches, regexes that can't be compiled here never do.
+    fn matches(&self, text: &str) -> bool {
+        match &self.kind {
+            PatternKind::Literal(literal) => literal == text,
+            PatternKind::Regex(regex) => PikeVM::new(&format!("^(?:{regex})$"))
+                .is_ok_and(|vm| vm.is_match(&mut vm.create_cache(), text)),
+        }
+    }
+}
+
+impl Analyzed<'_> {
+    fn token_patterns(&self, instance: Option<usize>) -> Vec<(&Ident, Vec<Pattern>)> {
+        self.automaton
+            .grammar()
+            .tokens()
+            .iter()
+            .map(|token| {
+                let ident = token.extras().id();
+                let patterns = self
+                    .token_attributes(ident, instance)
+                    .iter()
+                    .filter_map(Pattern::parse)
+                    .collect_vec();
+                (ident, patterns)
+            })
+            .collect_vec()
+    }
+
+    /// The lexer of the `index`-th pattern of `token` alone.
+    fn pattern_lexer(token: &Ident, index: usize) -> Ident {
+        format_ident!("__{}Pattern{}", token, index)
+    }
+
+    /// Whether a token lexed by a regex could be reinterpreted as another token lexed by one.
+    fn relexes_regexes(tokens: &[(&Ident, Vec<Pattern>)]) -> bool {
+        tokens
+            .iter()
+            .filter(|(_, patterns)| patterns.iter().any(Pattern::is_regex))
+            .count()
+            > 1
+    }
+
+    /// A lexer for every `#[regex]` a token can be reinterpreted with, which lexes the text of
+    /// another token again with that pattern alone. It shares the subpatterns and the source of
+    /// `Token`, not the rest of its `#[logos]` options.
+    pub(crate) fn pattern_lexers(
+        &self,
+        root_attributes: &[Attribute],
+        instance: Option<usize>,
+    ) -> Vec<TokenStream> {
+        let tokens = self.token_patterns(instance);
+        if !Self::relexes_regexes(&tokens) {
+            return Vec::new();
+        }
+        let logos_attributes = root_attributes
+            .iter()
+            .filter(|attr| {
+                attr.path().is_ident("logos")
+                    && attr
+                        .parse_args_with(|input: ParseStream| {
+                            let option = input.parse::<Ident>()?;
+                            input.parse::<TokenStream>()?;
+                            Ok(option)
+                        })
+                        .is_ok_and(|option| {
+                            option == "subpattern" || option == "source" || option == "utf8"
+                        })
+            })
+            .collect_vec();
+        tokens
+            .iter()
+            .flat_map(|(ident, patterns)| {
+                patterns
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, pattern)| pattern.is_regex() && pattern.callback.is_some())
+                    .map(|(index, pattern)| {
+                        let lexer = Self::pattern_lexer(ident, index);
+                        let matcher = &pattern.matcher;
+                        quote! {
+                            #[doc(hidden)]
+                            #[derive(Logos)]
+                            #[allow(dead_code, non_camel_case_types)]
+                            #(#logos_attributes)*
+                            enum #lexer {
+                                #matcher
+                                Match,
+                            }
+                        }
+                    })
+                    .collect_vec()
+            })
+            .collect()
+    }
+
+    /// `Token::reinterpret`, which tries the other tokens the text of a token could have been
+    /// lexed as: a keyword matched by the pattern of another token, a text of another token
+    /// matched by the pattern of this one, or, between two regexes, the text lexed again from
+    /// its start by the pattern of the other token, which has to take all of it.
+    pub(crate) fn reinterpret_method(&self, instance: Option<usize>) -> TokenStream {
+        let tokens = self.token_patterns(instance);
+        let relexes_regexes = Self::relexes_regexes(&tokens);
+        let arms = tokens
+            .iter()
+            .map(|(ident, patterns)| {
+                let is_regex = patterns.iter().any(Pattern::is_regex);
+                let candidates = tokens
+                    .iter()
+                    .filter(|(other, _)| other != ident)
+                    .flat_map(|(other, other_patterns)| {
+                        let own_texts =
+                            patterns
+                                .iter()
+                                .filter_map(Pattern::literal)
+                                .flat_map(|text| {
+                                    other_patterns
+                                        .iter()
+                                        .filter(move |pattern| pattern.matches(text))
+                                        .map(move |pattern| (text, pattern))
+                                });
+                        let other_texts = other_patterns.iter().filter_map(|pattern| {
+                            let text = pattern.literal()?;
+                            patterns
+                                .iter()
+                                .any(|own| own.literal().is_none() && own.matches(text))
+                                .then_some((text, pattern))
+                        });
+                        let fixed = own_texts
+                            .chain(other_texts)
+                            .filter_map(|(text, pattern)| Some((text, pattern.callback.as_ref()?)))
+                            .map(|(text, callback)| {
+                                let text = LitByteStr::new(text.as_bytes(), Span::call_site());
+                                let matches = quote!(AsRef::<[u8]>::as_ref(text) == #text);
+                                self.reinterpretation(matches, other, callback)
+                            });
+                        let relexed = other_patterns
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, pattern)| relexes_regexes && is_regex && pattern.is_regex())
+                            .filter_map(|(index, pattern)| Some((index, pattern.callback.as_ref()?)))
+                            .map(|(index, callback)| {
+                                let lexer = Self::pattern_lexer(other, index);
+                                let matches = quote! {{
+                                    let mut pattern = <#lexer as logos::Logos<'source>>::lexer(text);
+                                    matches!(pattern.next(), Some(Ok(_)))
+                                        && pattern.span().end == text.len()
+                                }};
+                                self.reinterpretation(matches, other, callback)
+                            });
+                        fixed.chain(relexed).collect_vec()
+                    })
+                    .collect_vec();
+                (ident, candidates)
+            })
+            .filter(|(_, candidates)| !candidates.is_empty())
+            .map(|(ident, candidates)| quote!(Self::#ident(_) => { #(#candidates)* }))
+            .collect_vec();
+        if arms.is_empty() {
+            return quote! {
+                fn reinterpret<'source>(
+                    &self,
+                    _source: &'source <Self as logos::Logos<'source>>::Source,
+                    _span: core::ops::Range<usize>,
+                    _accepts: impl FnMut(&Self) -> bool,
+                ) -> Option<Self> {
+                    None
+                }
+            };
+        }
+        quote! {
+            fn reinterpret<'source>(
+                &self,
+                source: &'source <Self as logos::Logos<'source>>::Source,
+                span: core::ops::Range<usize>,
+                mut accepts: impl FnMut(&Self) -> bool,
+            ) -> Option<Self> {
+                // gives the closures among the callbacks the type of their argument
+                fn run_callback<'source, Token: logos::Logos<'source>, Output>(
+                    lexer: &mut logos::Lexer<'source, Token>,
+                    callback: impl FnOnce(&mut logos::Lexer<'source, Token>) -> Output,
+                ) -> Output {
+                    callback(lexer)
+                }
+
+                let text = &source[span];
+                match self {
+                    #(#arms)*
+                    _ => {}
+                }
+                None
+            }
+        }
+    }
+
+    /// Builds `token` out of `text` with `callback` when `matches`, as the lexer would have.
+    fn reinterpretation(
+        &self,
+        matches: TokenStream,
+        token: &Ident,
+        callback: &Expr,
+    ) -> TokenStream {
+        let ty = self.symbol_type(token);
+        quote! {
+            if #matches {
+                let mut lexer = <Self as logos::Logos<'source>>::lexer(text);
+                lexer.next();
+                if let Some(candidate) =
+                    semasia_parser::CallbackOutput::<#ty>::into_value(run_callback(&mut lexer, #callback))
+                        .map(Self::#token)
+                {
+                    if accepts(&candidate) {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use itertools::Itertools;
+use proc_macro_error::emit_error;
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{ToTokens, quote};
+use semasia_dyn_grammar::Generic;
+use syn::{
+    Attribute, Expr, ExprClosure, Generics, Ident, Item, ItemEnum, ItemStruct, ItemType, Token,
+    Type, parse::ParseStream, parse_quote, punctuated::Punctuated,
+};
+
+use crate::constructor::Constructor;
+
+/// `Type => pattern`, where the pattern is either a literal or a `(literal, callback)` tuple.
+struct InstancePattern {
+    instance: Type,
+    pattern: Expr,
+}
+
+impl syn::parse::Parse for InstancePattern {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let instance = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let pattern = input.parse()?;
+        Ok(Self { instance, pattern })
+    }
+}
+
+struct GenericProduction {
+    name: Ident,
+    generics: Generics,
+    head: Type,
+    body: Type,
+    action: Option<ExprClosure>,
+}
+
+impl syn::parse::Parse for GenericProduction {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let generics = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let head = input.parse()?;
+        input.parse::<Token![->]>()?;
+        let body = input.parse()?;
+        let action = if input.is_empty() {
+            None
+        } else {
+            input.parse::<Token![,]>()?;
+            Some(input.parse()?)
+        };
+        Ok(Self {
+            name,
+            generics,
+            head,
+            body,
+            action,
+        })
+    }
+}
+
+impl Constructor {
+    pub(crate) fn symbol_type_parameter(item: &Item) -> Option<Ident> {
+        let generics = match item {
+            Item::Type(ItemType { generics, .. })
+            | Item::Struct(ItemStruct { generics, .. })
+            | Item::Enum(ItemEnum { generics, .. }) => generics,
+            _ => return None,
+        };
+        let mut parameters = generics.type_params();
+        let parameter = parameters.next()?;
+        if let Some(extra) = parameters.next() {
+            emit_error!(extra, "grammar symbols can have only one type parameter");
+        }
+        Some(parameter.ident.clone())
+    }
+
+    /// Splits the `#[token(Type => ...)]` and `#[regex(Type => ...)]` attributes from the
+    /// ones shared by every instance of the grammar.
+    pub(crate) fn split_instance_attributes(
+        attributes: Vec<Attribute>,
+    ) -> (Vec<Attribute>, Vec<(Type, Attribute)>) {
+        let mut shared = Vec::new();
+        let mut per_instance = Vec::new();
+        for attribute in attributes {
+            let Ok(patterns) = attribute
+                .parse_args_with(Punctuated::<InstancePattern, Token![,]>::parse_terminated)
+            else {
+                shared.push(attribute);
+                continue;
+            };
+            let path = attribute.path();
+            for InstancePattern { instance, pattern } in patterns {
+                let arguments = match pattern {
+                    Expr::Tuple(tuple) => tuple.elems.into_token_stream(),
+                    Expr::Paren(paren) => {
+                        let pattern = paren.expr;
+                        quote!(#pattern, parse)
+                    }
+                    pattern => quote!(#pattern, parse),
+                };
+                per_instance.push((instance, parse_quote!(#[#path(#arguments)])));
+            }
+        }
+        (shared, per_instance)
+    }
+
+    pub(crate) fn generic(
+        generic_symbols: Vec<(Ident, Ident)>,
+        instance_attributes: Vec<(Ident, Vec<(Type, Attribute)>)>,
+    ) -> Option<Generic> {
+        let Some((_, parameter)) = generic_symbols.first().cloned() else {
+            if let Some((token, _)) = instance_attributes.first() {
+                emit_error!(
+                    token, "patterns for each type are only allowed on generic tokens";
+                    help = "add a type parameter, e.g. `pub type {}<T> = T;`", token
+                );
+            }
+            return None;
+        };
+        for (symbol, symbol_parameter) in &generic_symbols {
+            if symbol_parameter != &parameter {
+                emit_error!(
+                    symbol_parameter, "all the symbols of a grammar have to use the same type parameter";
+                    note = "`{}` uses `{}` while the grammar uses `{}`", symbol, symbol_parameter, parameter
+                );
+            }
+        }
+
+        let (instance_attributes, non_generic): (Vec<_>, Vec<_>) = instance_attributes
+            .into_iter()
+            .partition(|(token, _)| generic_symbols.iter().any(|(symbol, _)| symbol == token));
+        for (token, _) in non_generic {
+            emit_error!(
+                token, "patterns for each type are only allowed on generic tokens";
+                help = "add the type parameter `{}` to `{}`", parameter, token
+            );
+        }
+
+        let instances = instance_attributes
+            .iter()
+            .flat_map(|(_, attributes)| attributes.iter().map(|(instance, _)| instance))
+            .unique_by(|instance| instance.to_token_stream().to_string())
+            .cloned()
+            .collect_vec();
+        if instances.is_empty() {
+            emit_error!(
+                parameter, "a generic grammar needs at least one type to be instantiated with";
+                help = "give the patterns of a generic token for each type, e.g. `#[regex(i64 => r\"\\d+\")]`"
+            );
+        }
+
+        let token_attributes = instance_attributes
+            .into_iter()
+            .map(|(token, attributes)| {
+                let per_instance = instances
+                    .iter()
+                    .map(|instance| {
+                        let instance_name = instance.to_token_stream().to_string();
+                        let instance_attributes = attributes
+                            .iter()
+                            .filter(|(other, _)| {
+                                other.to_token_stream().to_string() == instance_name
+                            })
+                            .map(|(_, attribute)| attribute.clone())
+                            .collect_vec();
+                        if instance_attributes.is_empty() {
+                            emit_error!(
+                                token,
+                                "`{}` has no pattern for `{}`",
+                                token,
+                                instance_name
+                            );
+                        }
+                        instance_attributes
+                    })
+                    .collect();
+                (token, per_instance)
+            })
+            .collect();
+
+        Some(Generic {
+            parameter,
+            symbols: generic_symbols
+                .into_iter()
+                .map(|(symbol, _)| symbol)
+                .collect(),
+            instances,
+            token_attributes,
+        })
+    }
+
+    pub(crate) fn is_generic_production(item: &Item) -> bool {
+        let Item::Macro(mac) = item else {
+            return false;
+        };
+        mac.mac.path.is_ident("production")
+            && mac
+                .mac
+                .parse_body::<GenericProduction>()
+                .is_ok_and(|production| production.generics.type_params().next().is_some())
+    }
+
+    /// Replaces a generic `production!` with its struct and an implementation of `Production`
+    /// for each instance, the type parameter is substituted in the semantic action too.
+    pub(crate) fn monomorphize_production(item: &mut Item, generic: &Generic) -> Option<Ident> {
+        let Item::Macro(mac) = item else {
+            return None;
+        };
+        let production = mac.mac.parse_body::<GenericProduction>().ok()?;
+        let parameter = production.generics.type_params().next()?.ident.clone();
+        if parameter != generic.parameter {
+            emit_error!(
+                parameter, "all the symbols of a grammar have to use the same type parameter";
+                note = "the grammar uses `{}`", generic.parameter
+            );
+        }
+        let GenericProduction {
+            name, head, body, ..
+        } = &production;
+        let attributes = &mac.attrs;
+        let doc = format!(
+            "Production: `{} -> {}`",
+            head.to_token_stream(),
+            body.to_token_stream()
+        );
+        let impls = generic.instances.iter().map(|instance| {
+            let instance = instance.to_token_stream();
+            let head = substitute(head.to_token_stream(), &parameter, &instance);
+            let body = substitute(body.to_token_stream(), &parameter, &instance);
+            let synthesize = match &production.action {
+                Some(action) => {
+                    let inputs = action.inputs.iter().collect_vec();
+                    let (ctx, param) = match inputs.as_slice() {
+                        [ctx, param] => (quote!(#ctx), quote!(#param)),
+                        [param] => (quote!(_), quote!(#param)),
+                        _ => {
+                            emit_error!(
+                                action,
+                                "the semantic action takes the body and optionally the context"
+                            );
+                            (quote!(_), quote!(_))
+                        }
+                    };
+                    let action = substitute(action.body.to_token_stream(), &parameter, &instance);
+                    quote! {
+                        fn synthesize(#ctx: &mut Self::Ctx, #param: Self::Body) -> Self::Head {
+                            #action
+                        }
+                    }
+                }
+                None => quote! {
+                    fn synthesize(_: &mut Self::Ctx, body: Self::Body) -> Self::Head {
+                        body.into()
+                    }
+                },
+            };
+            quote! {
+                impl semasia::Production for #name<#instance> {
+                    type Head = #head;
+                    type Body = #body;
+                    type Ctx = __CompilerContext;
+
+                    #synthesize
+                }
+            }
+        });
+        *item = Item::Verbatim(quote! {
+            #(#attributes)*
+            #[doc = #doc]
+            pub struct #name<#parameter>(core::marker::PhantomData<#parameter>);
+
+            #(#impls)*
+        });
+        Some(name.clone())
+    }
+}
+
+fn substitute(tokens: TokenStream, parameter: &Ident, instance: &TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|tree| match tree {
+            TokenTree::Ident(ident) if &ident == parameter => instance.clone(),
+            TokenTree::Group(group) => {
+                let mut substituted = Group::new(
+                    group.delimiter(),
+                    substitute(group.stream(), parameter, instance),
+                );
+                substituted.set_span(group.span());
+                TokenTree::Group(substituted).into()
+            }
+            tree => tree.into(),
+        })
+        .collect()
+}
@@ -1,32 +1,64 @@
 use semasia_auto_productions_parser::AutoProductionsEnum;
 use semasia_dyn_grammar::{
-    EnrichedBaseProduction, EnrichedGrammar, EnrichedNonTerminal, EnrichedToken,
-    conflicts::Associativity, grammar::Body, lalr::LalrAutomaton,
+    Context, EnrichedBaseProduction, EnrichedGrammar, EnrichedNonTerminal, EnrichedProduction,
+    EnrichedToken, SubGrammar, TokenValue,
+    conflicts::Associativity,
+    grammar::{Body, Production, Symbol},
+    lalr::LalrAutomaton,
     symbolic_grammar::SymbolicGrammar,
 };
 use semasia_ebnf_parser::EbnfProduction;
 use itertools::Itertools;
 use proc_macro_error::{abort_if_dirty, emit_call_site_error, emit_call_site_warning, emit_error};
-use quote::quote;
-use std::collections::HashSet;
+use quote::{ToTokens, format_ident, quote};
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+};
 use syn::{
-    Attribute, Ident, Item, ItemEnum, ItemStruct, ItemType, ItemUse, LitInt, Meta, Type, UseGroup,
-    UseTree, parse::Parser, parse_quote,
+    Attribute, ExprClosure, Ident, Item, ItemEnum, ItemStruct, ItemType, ItemUse, LitInt, LitStr,
+    Meta, Type, TypePath, UseGroup, UseTree, parse_quote, spanned::Spanned,
 };
 
 use crate::constructor::*;
 
+/// A production whose body still refers to symbols by the types written in `production!`.
+type ExtractedProduction = Production<Ident, Ident, Type, Option<usize>>;
+
+/// Tokens of sub-grammars merged into a token lexed the same way, by their name in this grammar.
+type MergedTokens = HashMap<Ident, EnrichedToken>;
+
 impl Constructor {
-    pub fn extract(self, items: &mut [Item]) -> Extracted {
+    pub fn extract(self, items: &mut Vec<Item>) -> Extracted {
         let mut tokens = Vec::new();
         let mut non_terminals = Vec::new();
         let mut ebnf_extra_non_terminals = HashSet::new();
         let mut productions = Vec::new();
         let mut start_symbol = None;
+        let mut entry_points = Vec::new();
         let mut compiler_ctx: Option<Ident> = None;
+        let mut sub_grammars = Vec::new();
+        let mut generic_symbols = Vec::new();
+        let mut instance_attributes = Vec::new();
+        let mut symbol_names = HashSet::new();
+        let mut aliases: HashMap<String, Vec<Ident>> = HashMap::new();
+        let mut predicates = Vec::new();
+        let mut reclassify = None;
+        let mut unit_conversions = Vec::new();
+
+        let helpers = items
+            .iter_mut()
+            .flat_map(Self::desugar_production)
+            .collect_vec();
+        items.extend(helpers);
 
         for item in items.iter_mut() {
-            if let Some(ctx) = Self::extract_context(item) {
+            let type_parameter = Self::symbol_type_parameter(item);
+            let predicate = Self::extract_predicate(item);
+            if let Some((module, mut sub_items)) = Self::extract_sub_grammar(item) {
+                let Extracted { grammar } = Constructor.extract(&mut sub_items);
+                sub_grammars.push((module, grammar));
+            } else if let Some(ctx) = Self::extract_context(item) {
                 if let Some(old_ctx) = compiler_ctx.as_ref() {
                     emit_error!(
                         old_ctx, "you can only declare one compilation context";
@@ -34,11 +66,42 @@ impl Constructor {
                     );
                 }
                 compiler_ctx = Some(ctx);
+                reclassify = Self::extract_reclassify(item);
             } else if let Some(token) = Self::extract_token(item) {
+                let token = token.map(
+                    |id, (attributes, priority, associativity, display, value)| {
+                        let (attributes, per_instance) =
+                            Self::split_instance_attributes(attributes);
+                        if !per_instance.is_empty() {
+                            instance_attributes.push((id.clone(), per_instance));
+                        }
+                        (id, (attributes, priority, associativity, display, value))
+                    },
+                );
+                if let Some(parameter) = type_parameter {
+                    generic_symbols.push((token.id().clone(), parameter));
+                }
+                symbol_names.insert(token.id().to_string());
+                if let Some(alias) = Self::symbol_alias(item) {
+                    aliases.entry(alias).or_default().push(token.id().clone());
+                }
                 tokens.push(token);
-            } else if let Some((non_terminal, is_start, is_auto_productions)) =
+            } else if let Some((non_terminal, is_start, is_entry_point, is_auto_productions)) =
                 Self::extract_non_terminal(item)
             {
+                if let Some(parameter) = type_parameter {
+                    generic_symbols.push((non_terminal.id().clone(), parameter));
+                }
+                symbol_names.insert(non_terminal.id().to_string());
+                if let Some(alias) = Self::symbol_alias(item) {
+                    aliases
+                        .entry(alias)
+                        .or_default()
+                        .push(non_terminal.id().clone());
+                }
+                if is_entry_point {
+                    entry_points.push(non_terminals.len());
+                }
                 if is_start {
                     if let Some(cur_start) = start_symbol {
                         let start_nt: &EnrichedNonTerminal = &non_terminals[cur_start];
@@ -54,10 +117,13 @@ impl Constructor {
                     let auto_productions_enum =
                         AutoProductionsEnum::try_from(&*item).unwrap();
                     let new_productions = auto_productions_enum.compile();
-                    productions.extend(new_productions);
+                    productions.extend(new_productions.into_iter().map(Self::typed_production));
                 }
                 non_terminals.push(non_terminal);
             } else if let Some(production) = Self::extract_production(item) {
+                if let Some(predicate) = predicate {
+                    predicates.push((production.id().clone(), predicate));
+                }
                 productions.push(production);
             } else if let Some(ebnf) = Self::extract_ebnf_production(item) {
                 let extra_prods = ebnf.compile().0.into_iter().map(Into::into).collect_vec();
@@ -66,11 +132,58 @@ impl Constructor {
                     .map(EnrichedBaseProduction::head)
                     .map(|head| EnrichedNonTerminal::new(head.clone(), ()));
                 ebnf_extra_non_terminals.extend(extra_nts);
-                productions.extend(extra_prods);
+                productions.extend(extra_prods.into_iter().map(Self::typed_production));
+            }
+        }
+
+        let mut generic = Self::generic(generic_symbols, instance_attributes);
+        if let Some(generic) = generic.as_mut() {
+            for item in items.iter_mut() {
+                if Self::is_generic_production(item)
+                    && let Some(production) = Self::monomorphize_production(item, generic)
+                {
+                    generic.symbols.push(production);
+                }
             }
         }
 
-        if non_terminals.is_empty() || productions.is_empty() {
+        let (sub_grammars, sub_productions, merged_tokens) = Self::merge_sub_grammars(
+            sub_grammars,
+            &mut tokens,
+            &mut non_terminals,
+            &mut predicates,
+            &mut unit_conversions,
+        );
+        let compiler_ctx = Self::unify_contexts(compiler_ctx, &sub_grammars);
+
+        let productions = productions
+            .into_iter()
+            .map(|production| {
+                production.map(|id, head, body, extras| {
+                    let body = body
+                        .into_iter()
+                        .enumerate()
+                        .map(|(position, ty)| {
+                            let symbol =
+                                Self::resolve_symbol(ty, &symbol_names, &aliases, &sub_grammars);
+                            match Self::lexed_token(
+                                &merged_tokens,
+                                &id,
+                                position,
+                                &symbol,
+                                &mut unit_conversions,
+                            ) {
+                                Some(token) => token.id().clone(),
+                                None => symbol,
+                            }
+                        })
+                        .collect();
+                    (id, head, body, extras)
+                })
+            })
+            .collect_vec();
+
+        if non_terminals.is_empty() || (productions.is_empty() && sub_productions.is_empty()) {
             emit_call_site_error!(
             "every grammar has to have some non-terminals and productions.";
             note = "Found non-terminals: [{}], tokens: [{}], productions: [{}]",
@@ -89,25 +202,265 @@ impl Constructor {
             }
             0
         });
+        entry_points.retain(|&entry_point| entry_point != start_symbol);
 
         non_terminals.extend(ebnf_extra_non_terminals);
 
         let productions = productions
             .into_iter()
             .map(|prod| prod.into_production(&tokens, &non_terminals))
+            .chain(sub_productions)
             .collect();
 
         let grammar = EnrichedGrammar::new(
             tokens,
             non_terminals.into_iter().unique().collect(),
             start_symbol,
+            entry_points,
             productions,
-            semasia_dyn_grammar::Context(compiler_ctx),
+            Context {
+                compiler_ctx,
+                sub_grammars,
+                generic,
+                predicates,
+                reclassify,
+                unit_conversions,
+            },
         );
 
         Extracted { grammar }
     }
 
+    fn extract_sub_grammar(item: &Item) -> Option<(Ident, Vec<Item>)> {
+        let Item::Mod(module) = item else {
+            return None;
+        };
+        let attribute = module.attrs.iter().find(|attr| {
+            attr.path()
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "grammar")
+        })?;
+        let arguments = match &attribute.meta {
+            Meta::List(list) => list.tokens.clone(),
+            _ => Default::default(),
+        };
+        let mut module = module.clone();
+        if let (_, Some(path)) = crate::grammar_arguments(arguments) {
+            crate::load_module_file(&mut module, &path);
+        }
+        let (_, items) = module.content?;
+        Some((module.ident, items))
+    }
+
+    /// Adds the symbols of the nested grammars to the ones of this grammar. They are named after
+    /// the path of their module, so that grammars can use the same names, and a token lexed
+    /// exactly like one that's already there is merged into it.
+    fn merge_sub_grammars(
+        sub_grammars: Vec<(Ident, EnrichedGrammar)>,
+        tokens: &mut Vec<EnrichedToken>,
+        non_terminals: &mut Vec<EnrichedNonTerminal>,
+        predicates: &mut Vec<(Ident, ExprClosure)>,
+        unit_conversions: &mut Vec<(Ident, usize, Ident)>,
+    ) -> (Vec<SubGrammar>, Vec<EnrichedProduction>, MergedTokens) {
+        let mut merged = Vec::new();
+        let mut merged_tokens = MergedTokens::new();
+        let mut merged_productions = Vec::new();
+        for (module, grammar) in sub_grammars {
+            let rename =
+                |ident: &Ident| format_ident!("{}__{}", module, ident, span = ident.span());
+            let Context {
+                compiler_ctx,
+                sub_grammars: nested,
+                generic,
+                predicates: sub_predicates,
+                reclassify,
+                unit_conversions: sub_conversions,
+            } = grammar.extras();
+            if generic.is_some() {
+                emit_error!(
+                    module,
+                    "a generic grammar can't be merged into another grammar"
+                );
+            }
+            if let Some(reclassify) = reclassify {
+                emit_error!(
+                    reclassify, "only the outermost grammar can reclassify tokens";
+                    note = module.span() => "`{}` is merged into another grammar", module
+                );
+            }
+            let nested = nested
+                .iter()
+                .map(|sub_grammar| SubGrammar {
+                    path: iter::once(module.clone())
+                        .chain(sub_grammar.path.iter().cloned())
+                        .collect(),
+                    symbols: sub_grammar.symbols.clone(),
+                    compiler_ctx: sub_grammar.compiler_ctx.clone(),
+                    productions: sub_grammar.productions.clone(),
+                })
+                .collect_vec();
+            let nested_items = nested
+                .iter()
+                .flat_map(|sub_grammar| sub_grammar.items().map(|(local, _)| local))
+                .collect::<HashSet<_>>();
+            let own = |idents: Vec<&Ident>| {
+                idents
+                    .into_iter()
+                    .filter(|ident| !nested_items.contains(&rename(ident)))
+                    .cloned()
+                    .collect_vec()
+            };
+            let symbols = own(grammar
+                .tokens()
+                .iter()
+                .map(EnrichedToken::id)
+                .chain(grammar.non_terminals().iter().map(EnrichedNonTerminal::id))
+                .collect());
+            let productions = own(grammar.productions().iter().map(|prod| prod.id()).collect());
+            merged.extend(nested);
+            merged.push(SubGrammar {
+                path: vec![module.clone()],
+                symbols,
+                compiler_ctx: compiler_ctx.clone(),
+                productions,
+            });
+
+            predicates.extend(
+                sub_predicates
+                    .iter()
+                    .map(|(production, predicate)| (rename(production), predicate.clone())),
+            );
+            unit_conversions.extend(
+                sub_conversions.iter().map(|(production, position, unit)| {
+                    (rename(production), *position, rename(unit))
+                }),
+            );
+            for token in grammar.tokens() {
+                let name = token.id();
+                let token = token.clone().map(|id, extras| (rename(&id), extras));
+                match tokens.iter().find(|other| Self::lexed_alike(other, &token)) {
+                    Some(other) => {
+                        Self::check_mergeable(name, &token, other);
+                        merged_tokens.insert(token.id().clone(), other.clone());
+                    }
+                    None => tokens.push(token),
+                }
+            }
+            non_terminals.extend(
+                grammar
+                    .non_terminals()
+                    .iter()
+                    .map(|non_terminal| EnrichedNonTerminal::new(rename(non_terminal.id()), ())),
+            );
+            let productions = grammar.into_productions().into_iter().map(|production| {
+                production.map(|id, head, body, extras| {
+                    let id = rename(&id);
+                    let body = body
+                        .into_iter()
+                        .enumerate()
+                        .map(|(position, symbol)| match symbol {
+                            Symbol::Token(token) => {
+                                let token = token.map(|id, extras| (rename(&id), extras));
+                                Symbol::Token(
+                                    Self::lexed_token(
+                                        &merged_tokens,
+                                        &id,
+                                        position,
+                                        token.id(),
+                                        unit_conversions,
+                                    )
+                                    .cloned()
+                                    .unwrap_or(token),
+                                )
+                            }
+                            Symbol::NonTerminal(non_terminal) => Symbol::NonTerminal(
+                                EnrichedNonTerminal::new(rename(non_terminal.id()), ()),
+                            ),
+                        })
+                        .collect();
+                    (
+                        id,
+                        EnrichedNonTerminal::new(rename(head.id()), ()),
+                        body,
+                        extras,
+                    )
+                })
+            });
+            merged_productions.extend(productions);
+        }
+        (merged, merged_productions, merged_tokens)
+    }
+
+    /// Whether two tokens have the same `#[token]` and `#[regex]` attributes, the lexer couldn't
+    /// tell them apart.
+    fn lexed_alike(token: &EnrichedToken, other: &EnrichedToken) -> bool {
+        let patterns = |token: &EnrichedToken| {
+            let attributes = &token.extras().0;
+            quote!(#(#attributes)*).to_string()
+        };
+        !token.extras().0.is_empty() && patterns(token) == patterns(other)
+    }
+
+    /// Tokens lexed alike can be merged when their values are made the same way: both are unit
+    /// structs or aliases of the same type.
+    fn check_mergeable(name: &Ident, token: &EnrichedToken, other: &EnrichedToken) {
+        let (_, priority, associativity, _, value) = token.extras();
+        let (_, other_priority, other_associativity, _, other_value) = other.extras();
+        if value != other_value || *value == TokenValue::Own {
+            emit_error!(
+                name, "`{}` is lexed like a token of another grammar but has a different type", name;
+                note = other.id().span() => "the other token is declared here";
+                help = "declare both as unit structs or as aliases of the same type"
+            );
+        } else if priority != other_priority || associativity != other_associativity {
+            emit_error!(
+                name, "`{}` is lexed like a token of another grammar but has a different precedence", name;
+                note = other.id().span() => "the other token is declared here"
+            );
+        }
+    }
+
+    /// The token a body element is lexed as when it was merged into another one. A unit struct
+    /// is then made again as the one the production expects.
+    fn lexed_token<'a>(
+        merged_tokens: &'a MergedTokens,
+        production: &Ident,
+        position: usize,
+        symbol: &Ident,
+        unit_conversions: &mut Vec<(Ident, usize, Ident)>,
+    ) -> Option<&'a EnrichedToken> {
+        let token = merged_tokens.get(symbol)?;
+        let converted = unit_conversions
+            .iter()
+            .any(|(other, other_position, _)| other == production && *other_position == position);
+        if token.extras().4 == TokenValue::Unit && !converted {
+            unit_conversions.push((production.clone(), position, symbol.clone()));
+        }
+        Some(token)
+    }
+
+    /// Productions of the nested grammars run with the context of this grammar, so all of them
+    /// have to either share it or not use a context at all. Contexts with the same name are
+    /// assumed to be the same type.
+    fn unify_contexts(compiler_ctx: Option<Ident>, sub_grammars: &[SubGrammar]) -> Option<Ident> {
+        let mut unified = compiler_ctx.map(|ctx| (ctx.clone(), ctx));
+        for sub_grammar in sub_grammars {
+            let Some(sub_ctx) = &sub_grammar.compiler_ctx else {
+                continue;
+            };
+            match &unified {
+                Some((ctx, _)) if ctx != sub_ctx => emit_error!(
+                    sub_ctx, "sub-grammars have to use the same compilation context as their parent";
+                    note = ctx.span() => "the compilation context is `{}`", ctx
+                ),
+                Some(_) => {}
+                None => unified = Some((sub_ctx.clone(), sub_grammar.local_name(sub_ctx))),
+            }
+        }
+        unified.map(|(_, ty)| ty)
+    }
+
     fn extract_ident_from_use_tree(tree: &mut UseTree) -> Option<Ident> {
         match tree {
             UseTree::Path(use_path) => Self::extract_ident_from_use_tree(&mut use_path.tree),
@@ -132,8 +485,12 @@ impl Constructor {
         }
     }
 
-    fn is_marker(item: &Item) -> bool {
-        matches!(item, Item::Struct(str) if matches!(str.fields, syn::Fields::Unit))
+    fn token_value(item: &Item) -> TokenValue {
+        match item {
+            Item::Struct(str) if matches!(str.fields, syn::Fields::Unit) => TokenValue::Unit,
+            Item::Type(ItemType { ty, .. }) => TokenValue::Alias(ty.to_token_stream().to_string()),
+            _ => TokenValue::Own,
+        }
     }
 
     fn extract_context(item: &mut Item) -> Option<Ident> {
@@ -151,25 +508,16 @@ impl Constructor {
     }
 
     fn extract_token(item: &mut Item) -> Option<EnrichedToken> {
-        let is_marker = Self::is_marker(item);
+        let value = Self::token_value(item);
         let (attrs, ident) = Self::extract_info(item)?;
+        let literal_display = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("token"))
+            .find_map(|attr| attr.parse_args_with(Self::parse_token_literal).ok())
+            .map(|literal| format!("'{literal}'"));
         let token_attrs = attrs
             .extract_if(.., |attr| {
-                if !attr.path().is_ident("token") && !attr.path().is_ident("regex") {
-                    return false;
-                }
-                if !is_marker {
-                    return true;
-                }
-                if let Meta::List(raw_list) = &mut attr.meta {
-                    let parser =
-                        syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
-                    if let Ok(mut list) = parser.parse2(raw_list.tokens.clone()) {
-                        list.insert(1, parse_quote!(|_| #ident));
-                        raw_list.tokens = quote!(#list);
-                    }
-                }
-                true
+                attr.path().is_ident("token") || attr.path().is_ident("regex")
             })
             .collect_vec();
         (!token_attrs.is_empty()).then(|| {
@@ -213,36 +561,58 @@ impl Constructor {
                     None => true,
                 }
             });
+            let mut res_display = None;
+            attrs.retain(|attr| {
+                if !attr.path().is_ident("display") {
+                    return true;
+                }
+                match attr.parse_args::<LitStr>() {
+                    Ok(display) => {
+                        if res_display.is_some() {
+                            emit_error!(attr, "duplicated display attribute!");
+                        }
+                        res_display = Some(display.value());
+                    }
+                    Err(err) => {
+                        emit_error!(attr, "display name has to be a string literal"; note = "{}", err);
+                    }
+                }
+                false
+            });
+            let display = res_display
+                .or(literal_display)
+                .unwrap_or_else(|| ident.to_string());
+            // a bare `#[token]` is never lexed, it can only be produced by `#[reclassify]`
+            let token_attrs = token_attrs
+                .into_iter()
+                .filter(|attr| !matches!(attr.meta, Meta::Path(_)))
+                .collect();
             EnrichedToken::new(
                 ident,
-                (token_attrs, res_priority, res_assoc.unwrap_or_default()),
+                (
+                    token_attrs,
+                    res_priority,
+                    res_assoc.unwrap_or_default(),
+                    display,
+                    value,
+                ),
             )
         })
     }
 
-    fn extract_non_terminal(item: &mut Item) -> Option<(EnrichedNonTerminal, bool, bool)> {
+    fn parse_token_literal(input: syn::parse::ParseStream) -> syn::Result<String> {
+        let literal: LitStr = input.parse()?;
+        input.parse::<proc_macro2::TokenStream>()?;
+        Ok(literal.value())
+    }
+
+    fn extract_non_terminal(item: &mut Item) -> Option<(EnrichedNonTerminal, bool, bool, bool)> {
         let (attrs, ident) = Self::extract_info(item)?;
-        let id = attrs.iter().enumerate().find_map(|(i, attr)| {
-            if let Meta::Path(path) = &attr.meta
-                && path.is_ident("non_terminal")
-            {
-                return Some(i);
-            }
-            None
-        })?;
-        attrs.remove(id);
-        let mut is_start = false;
-        if let Some(id) = attrs.iter().enumerate().find_map(|(i, attr)| {
-            if let Meta::Path(path) = &attr.meta
-                && path.is_ident("start_symbol")
-            {
-                return Some(i);
-            }
-            None
-        }) {
-            attrs.remove(id);
-            is_start = true;
+        if !Self::take_marker_attribute(attrs, "non_terminal") {
+            return None;
         }
+        let is_start = Self::take_marker_attribute(attrs, "start_symbol");
+        let is_entry_point = Self::take_marker_attribute(attrs, "entry_point");
         let mut is_auto_productions = false;
         if attrs
             .iter()
@@ -253,11 +623,27 @@ impl Constructor {
         Some((
             EnrichedNonTerminal::new(ident, ()),
             is_start,
+            is_entry_point,
             is_auto_productions,
         ))
     }
 
-    fn extract_production(item: &mut Item) -> Option<EnrichedBaseProduction> {
+    fn take_marker_attribute(attrs: &mut Vec<Attribute>, name: &str) -> bool {
+        let Some(id) = attrs.iter().enumerate().find_map(|(i, attr)| {
+            if let Meta::Path(path) = &attr.meta
+                && path.is_ident(name)
+            {
+                return Some(i);
+            }
+            None
+        }) else {
+            return false;
+        };
+        attrs.remove(id);
+        true
+    }
+
+    fn extract_production(item: &mut Item) -> Option<ExtractedProduction> {
         match item {
             Item::Macro(mac) if mac.mac.path.is_ident("production") => {
                 let mut res_priority = None;
@@ -279,54 +665,211 @@ impl Constructor {
                     }
                     true
                 });
-                mac.mac
-                    .parse_body_with(|input: syn::parse::ParseStream| {
-                        let name = input.parse()?;
-                        input.parse::<syn::Token![:]>()?;
-                        let head = input.parse()?;
-                        input.parse::<syn::Token![->]>()?;
-                        let body = input.parse()?;
-                        let body = match body {
-                            Type::Path(type_path) => vec![
-                                type_path
-                                    .path
-                                    .get_ident()
-                                    .expect("use only one type")
-                                    .clone(),
-                            ],
-                            Type::Tuple(type_tuple) => type_tuple
-                                .elems
-                                .iter()
-                                .map(|t| {
-                                    let Type::Path(type_path) = t else {
-                                        panic!(
-                                            "body of production has to be a tuple of named types"
-                                        )
-                                    };
-                                    type_path
-                                        .path
-                                        .get_ident()
-                                        .expect("tuple of named types")
-                                        .clone()
-                                })
-                                .collect(),
-                            _ => panic!("type must be a unit, a single type or a tuple"),
-                        };
-                        let res = Ok(EnrichedBaseProduction::new(
-                            name,
-                            head,
-                            Body::new(body),
-                            res_priority,
+                let production = mac.mac.parse_body_with(|input: syn::parse::ParseStream| {
+                    let name = input.parse()?;
+                    input.parse::<syn::Generics>()?;
+                    input.parse::<syn::Token![:]>()?;
+                    let head = input.parse::<Type>()?;
+                    let head = match &head {
+                        Type::Path(type_path) => Self::symbol_ident(type_path),
+                        _ => None,
+                    }
+                    .ok_or_else(|| {
+                        syn::Error::new_spanned(&head, "head of production must be a non-terminal")
+                    })?;
+                    input.parse::<syn::Token![->]>()?;
+                    let body = match input.parse()? {
+                        Type::Tuple(type_tuple) => type_tuple.elems.into_iter().collect_vec(),
+                        Type::Paren(type_paren) => vec![*type_paren.elem],
+                        ty => vec![ty],
+                    };
+                    if let Some(ty) = body
+                        .iter()
+                        .find(|ty| !matches!(ty, Type::Path(TypePath { qself: None, .. })))
+                    {
+                        return Err(syn::Error::new_spanned(
+                            ty,
+                            "the body of a production can only contain symbols",
                         ));
-                        if input.is_empty() {
-                            return res;
-                        }
-                        input.parse::<syn::Token![,]>()?;
-                        input.parse::<syn::Expr>()?;
-                        res
-                    })
-                    .ok()
+                    }
+                    let res = Ok(ExtractedProduction::new(
+                        name,
+                        head,
+                        Body::new(body),
+                        res_priority,
+                    ));
+                    if input.is_empty() {
+                        return res;
+                    }
+                    input.parse::<syn::Token![,]>()?;
+                    input.parse::<syn::Expr>()?;
+                    res
+                });
+                match production {
+                    Ok(production) => Some(production),
+                    Err(err) => {
+                        emit_error!(err.span(), "production not well formed: {}", err);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The closure of the `#[predicate(|ctx, lookahead| ...)]` of a production, if it has one.
+    fn extract_predicate(item: &mut Item) -> Option<ExprClosure> {
+        let Item::Macro(mac) = item else {
+            return None;
+        };
+        if !mac.mac.path.is_ident("production") {
+            return None;
+        }
+        let mut predicate = None;
+        for attr in mac
+            .attrs
+            .extract_if(.., |attr| attr.path().is_ident("predicate"))
+        {
+            if predicate.is_some() {
+                emit_error!(attr, "duplicated predicate attribute!");
             }
+            match attr.parse_args::<ExprClosure>() {
+                Ok(closure) => predicate = Some(closure),
+                Err(err) => emit_error!(err.span(), "predicate not well formed: {}", err),
+            }
+        }
+        predicate
+    }
+
+    /// The closure of the `#[reclassify(|ctx, token| ...)]` next to `#[context]`, if there is one.
+    fn extract_reclassify(item: &mut Item) -> Option<ExprClosure> {
+        let (attrs, _) = Self::extract_info(item)?;
+        let id = attrs
+            .iter()
+            .position(|attr| attr.path().is_ident("reclassify"))?;
+        let attr = attrs.remove(id);
+        attr.parse_args()
+            .inspect_err(|err| emit_error!(err.span(), "reclassify not well formed: {}", err))
+            .ok()
+    }
+
+    fn typed_production(production: EnrichedBaseProduction) -> ExtractedProduction {
+        production.map_body(|symbol| parse_quote!(#symbol))
+    }
+
+    /// The type a symbol is declared as: the aliased type of a `type` or the path of a `use`.
+    fn symbol_alias(item: &Item) -> Option<String> {
+        match item {
+            Item::Type(ItemType { ty, .. }) => Some(ty.to_token_stream().to_string()),
+            Item::Use(ItemUse { tree, .. }) => {
+                let mut segments = Vec::new();
+                Self::use_tree_path(tree, &mut segments)
+                    .then(|| quote!(#(#segments)::*).to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn use_tree_path(tree: &UseTree, segments: &mut Vec<Ident>) -> bool {
+        match tree {
+            UseTree::Path(use_path) => {
+                segments.push(use_path.ident.clone());
+                Self::use_tree_path(&use_path.tree, segments)
+            }
+            UseTree::Name(use_name) => {
+                segments.push(use_name.ident.clone());
+                true
+            }
+            UseTree::Rename(use_rename) => {
+                segments.push(use_rename.ident.clone());
+                true
+            }
+            UseTree::Group(UseGroup { items, .. }) if items.len() == 1 => {
+                Self::use_tree_path(&items[0], segments)
+            }
+            _ => false,
+        }
+    }
+
+    /// Body elements are resolved by name, then by the type a symbol is declared as (so that
+    /// `Vec<Stmt>` finds `pub type Statements = Vec<Stmt>`), then by the path of a symbol of a
+    /// sub-grammar (`negations::OpenPar`) and at last by the last segment of their path, which
+    /// can also be the name of a symbol of a single sub-grammar.
+    fn resolve_symbol(
+        ty: Type,
+        symbol_names: &HashSet<String>,
+        aliases: &HashMap<String, Vec<Ident>>,
+        sub_grammars: &[SubGrammar],
+    ) -> Ident {
+        let written = ty.to_token_stream().to_string();
+        if symbol_names.contains(&written) {
+            return Ident::new(&written, ty.span());
+        }
+        match aliases.get(&written).map(Vec::as_slice) {
+            Some([symbol]) => return Ident::new(&symbol.to_string(), ty.span()),
+            Some([first, others @ ..]) => {
+                emit_error!(
+                    ty, "`{}` is the type of more than one symbol", written;
+                    note = "it could be `{}` or `{}`", first, others.iter().format("`, `");
+                    help = "use the name of the symbol instead"
+                );
+                return first.clone();
+            }
+            _ => {}
+        }
+        let Type::Path(type_path) = &ty else {
+            unreachable!("production bodies only contain paths")
+        };
+        let segments = type_path
+            .path
+            .segments
+            .iter()
+            .map(|segment| &segment.ident)
+            .skip_while(|ident| *ident == "self")
+            .collect_vec();
+        let (name, path) = segments
+            .split_last()
+            .expect("paths have at least one segment");
+        let in_sub_grammar = |sub_grammar: &SubGrammar| {
+            let mut local = sub_grammar.local_name(name);
+            local.set_span(ty.span());
+            local
+        };
+        if let Some(sub_grammar) = sub_grammars.iter().find(|sub_grammar| {
+            sub_grammar.path.iter().eq(path.iter().copied()) && sub_grammar.symbols.contains(name)
+        }) {
+            return in_sub_grammar(sub_grammar);
+        }
+        if symbol_names.contains(&name.to_string()) {
+            return Ident::new(&name.to_string(), ty.span());
+        }
+        let candidates = sub_grammars
+            .iter()
+            .filter(|sub_grammar| sub_grammar.symbols.contains(name))
+            .collect_vec();
+        match candidates.as_slice() {
+            [sub_grammar] => in_sub_grammar(sub_grammar),
+            [first, others @ ..] => {
+                let path = |sub_grammar: &SubGrammar| {
+                    format!("{}::{}", sub_grammar.path.iter().format("::"), name)
+                };
+                emit_error!(
+                    ty, "`{}` is declared by more than one sub-grammar", name;
+                    note = "it could be `{}` or `{}`", path(first),
+                        others.iter().map(|other| path(other)).format("`, `");
+                    help = "write the path of the symbol instead"
+                );
+                in_sub_grammar(first)
+            }
+            [] => Ident::new(&name.to_string(), ty.span()),
+        }
+    }
+
+    /// A symbol is named by a single identifier, possibly followed by the type parameter of
+    /// the grammar.
+    fn symbol_ident(type_path: &TypePath) -> Option<Ident> {
+        match type_path.path.segments.iter().collect_vec().as_slice() {
+            [segment] if type_path.qself.is_none() => Some(segment.ident.clone()),
             _ => None,
         }
     }
@@ -1,15 +1,24 @@
 use semasia_dyn_grammar::{
-    Context, EnrichedGrammar,
+    Context, EnrichedGrammar, Generic, TokenValue,
     parsing::tables::{EofTable, NonTerminalTable, TokenTable},
     symbolic_grammar::SymbolicSymbol,
 };
 use itertools::Itertools;
 use proc_macro::Span;
-use quote::quote;
-use syn::{Ident, Item, parse_quote};
+use proc_macro_error::abort;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, Item, Meta, parse::Parser, parse_quote};
 
 use crate::constructor::Analyzed;
 
+/// The attributes of the grammar module: most go on `Token`, `#[non_terminal_derive(..)]` become
+/// derives of `NonTerminal`.
+struct RootAttributes {
+    token: Vec<syn::Attribute>,
+    non_terminal: Vec<syn::Attribute>,
+}
+
 impl<'a> Analyzed<'a> {
     pub fn inject_items(
         &self,
@@ -17,13 +26,20 @@ impl<'a> Analyzed<'a> {
         internal_mod_name: Option<Ident>,
         root_attributes: Vec<syn::Attribute>,
     ) {
-        let mut items_to_add = Vec::new();
-        items_to_add.extend(Self::uses());
-        items_to_add.extend(self.token_enum(root_attributes));
-        items_to_add.extend(self.non_terminal_enum());
-        items_to_add.extend(self.production_enum());
-        items_to_add.extend(self.match_tables());
-        items_to_add.push(self.parser());
+        let (non_terminal_derives, root_attributes): (Vec<_>, Vec<_>) = root_attributes
+            .into_iter()
+            .partition(|attr| attr.path().is_ident("non_terminal_derive"));
+        let root_attributes = RootAttributes {
+            token: root_attributes,
+            non_terminal: non_terminal_derives
+                .into_iter()
+                .map(Self::non_terminal_derive)
+                .collect(),
+        };
+        let items_to_add = match &self.automaton.grammar().extras().generic {
+            Some(generic) => self.generic_items(generic, &root_attributes),
+            None => self.instance_items(&root_attributes, None),
+        };
 
         match internal_mod_name.as_ref() {
             Some(name) => items.push(parse_quote! {
@@ -39,6 +55,141 @@ impl<'a> Analyzed<'a> {
         }
 
         items.push(self.compiler_context());
+        items.extend(self.sub_grammar_aliases());
+    }
+
+    /// `#[non_terminal_derive(..)]` on the grammar module is a `#[derive(..)]` of `NonTerminal`.
+    fn non_terminal_derive(mut attr: syn::Attribute) -> syn::Attribute {
+        let Meta::List(list) = &mut attr.meta else {
+            abort!(
+                attr, "expected a list of derives";
+                help = "write `#[non_terminal_derive(Clone)]`"
+            );
+        };
+        list.path = parse_quote!(derive);
+        attr
+    }
+
+    fn instance_items(
+        &self,
+        root_attributes: &RootAttributes,
+        instance: Option<usize>,
+    ) -> Vec<Item> {
+        let mut items = Vec::new();
+        items.extend(Self::uses());
+        items.extend(self.token_enum(&root_attributes.token, instance));
+        items.extend(self.non_terminal_enum(&root_attributes.non_terminal));
+        items.extend(self.production_enum());
+        items.extend(self.match_tables());
+        items.extend(self.entry_points());
+        items.extend(self.parser());
+        items
+    }
+
+    /// Every instance of a generic grammar gets its own module with the same tables, the
+    /// `__Instance` trait maps each type to the items of its module.
+    fn generic_items(&self, generic: &Generic, root_attributes: &RootAttributes) -> Vec<Item> {
+        let modules = (0..generic.instances.len())
+            .map(|instance| format_ident!("__instance_{}", instance))
+            .collect_vec();
+        let instances = &generic.instances;
+        let instance_items = (0..instances.len()).map(|instance| {
+            let items = self.instance_items(root_attributes, Some(instance));
+            quote!(#(#items)*)
+        });
+        let entry_parsers = self
+            .automaton
+            .grammar()
+            .entry_points()
+            .map(|non_terminal| format_ident!("{}Parser", non_terminal.extras().id()))
+            .collect_vec();
+        let names = [
+            "Token",
+            "TokenKind",
+            "TokenSet",
+            "NonTerminal",
+            "ProductionName",
+            "Tables",
+            "Parser",
+        ]
+        .into_iter()
+        .map(|name| format_ident!("{}", name))
+        .chain(entry_parsers)
+        .collect_vec();
+        let impls = modules.iter().zip(instances).map(|(module, instance)| {
+            quote! {
+                impl __Instance for #instance {
+                    #(type #names = #module::#names;)*
+                    type ObservedParser<Obs> = #module::ObservedParser<Obs>;
+                }
+            }
+        });
+        let file: syn::File = parse_quote! {
+            #(
+                #[doc(hidden)]
+                pub mod #modules {
+                    use super::*;
+
+                    type __T = #instances;
+
+                    #instance_items
+                }
+            )*
+
+            #[doc(hidden)]
+            pub trait __Instance {
+                #(type #names;)*
+                type ObservedParser<Obs>;
+            }
+
+            #(#impls)*
+
+            #(pub type #names<T> = <T as __Instance>::#names;)*
+
+            pub type ObservedParser<T, Obs> = <T as __Instance>::ObservedParser<Obs>;
+        };
+        file.items
+    }
+
+    /// The type of a symbol, generic ones take the type of the instance being generated.
+    pub(crate) fn symbol_type(&self, symbol: &Ident) -> TokenStream {
+        match &self.automaton.grammar().extras().generic {
+            Some(generic) if generic.symbols.contains(symbol) => quote!(#symbol<__T>),
+            _ => quote!(#symbol),
+        }
+    }
+
+    /// The items of the sub-grammars are named after their path by type aliases.
+    fn sub_grammar_aliases(&self) -> Vec<Item> {
+        let Context { sub_grammars, .. } = self.automaton.grammar().extras();
+        sub_grammars
+            .iter()
+            .flat_map(|sub_grammar| {
+                let path = &sub_grammar.path;
+                sub_grammar.items().map(move |(local, item)| {
+                    parse_quote! {
+                        #[doc(hidden)]
+                        #[allow(dead_code, non_camel_case_types)]
+                        type #local = self::#(#path)::*::#item;
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The name a symbol or production is declared with, prefixed by the path of its module for
+    /// those of sub-grammars.
+    fn item_name(&self, ident: &Ident) -> String {
+        let Context { sub_grammars, .. } = self.automaton.grammar().extras();
+        sub_grammars
+            .iter()
+            .find_map(|sub_grammar| {
+                sub_grammar
+                    .items()
+                    .find(|(local, _)| local == ident)
+                    .map(|(_, item)| format!("{}::{}", sub_grammar.path.iter().format("::"), item))
+            })
+            .unwrap_or_else(|| ident.to_string())
     }
 
     fn uses() -> Vec<Item> {
@@ -49,50 +200,103 @@ impl<'a> Analyzed<'a> {
         file.items
     }
 
-    fn token_enum(&self, root_attributes: Vec<syn::Attribute>) -> Vec<Item> {
+    fn token_enum(&self, root_attributes: &[syn::Attribute], instance: Option<usize>) -> Vec<Item> {
         let tokens = self.automaton.grammar().tokens();
         let variants = tokens.iter().map(|token| {
             let ident = token.extras().id();
-            let attributes = &token.extras().extras().0;
+            let ty = self.symbol_type(ident);
+            let attributes = self.token_attributes(ident, instance);
             quote! {
                 #(#attributes)*
-                #ident(#ident)
+                #ident(#ty)
             }
         });
+        let reinterpret = self.reinterpret_method(instance);
+        let pattern_lexers = self.pattern_lexers(root_attributes, instance);
+        let displays = tokens.iter().map(|token| &token.extras().extras().3);
+        let kind_displays = displays.clone();
         let tokens: Vec<_> = tokens.iter().map(|token| token.extras().id()).collect();
+        let names = tokens.iter().map(|token| self.item_name(token));
         let counter = 0usize..;
         // TODO: find better way to parse
         let file: syn::File = parse_quote! {
-            fn parse<T: std::str::FromStr>(lex: &mut logos::Lexer<Token>) -> Option<T> {
+            fn parse<T: core::str::FromStr>(lex: &mut logos::Lexer<Token>) -> Option<T> {
                 lex.slice().parse().ok()
             }
 
-            fn to_string(lex: &mut logos::Lexer<Token>) -> String {
-                lex.slice().to_string()
+            fn to_string(lex: &mut logos::Lexer<Token>) -> semasia_parser::alloc::string::String {
+                lex.slice().into()
             }
 
-            fn make_default<T: std::default::Default>(lex: &mut logos::Lexer<Token>) -> T {
+            fn make_default<T: core::default::Default>(lex: &mut logos::Lexer<Token>) -> T {
                 T::default()
             }
 
             #[derive(Logos)]
+            #[allow(non_camel_case_types)]
             #(#root_attributes)*
             pub enum Token {
                 #(#variants,)*
             }
 
-            impl std::fmt::Display for Token {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            #(#pattern_lexers)*
+
+            impl core::fmt::Display for Token {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "{}", self.display_name())
+                }
+            }
+
+            impl core::fmt::Debug for Token {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                     match self {
-                        #(Self::#tokens (_) => write!(f, stringify!(#tokens)),)*
+                        #(Self::#tokens (_) => write!(f, #names),)*
                     }
                 }
             }
 
-            impl std::fmt::Debug for Token {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[allow(non_camel_case_types)]
+            pub enum TokenKind {
+                #(#tokens,)*
+            }
+
+            impl TokenKind {
+                pub const ALL: &'static [TokenKind] = &[#(Self::#tokens),*];
+
+                pub const fn id(self) -> usize {
+                    self as usize
+                }
+
+                pub const fn display_name(self) -> &'static str {
                     match self {
-                        #(Self::#tokens (_) => write!(f, stringify!(#tokens)),)*
+                        #(Self::#tokens => #kind_displays,)*
+                    }
+                }
+            }
+
+            impl core::fmt::Display for TokenKind {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "{}", self.display_name())
+                }
+            }
+
+            impl semasia_parser::SymbolKind for TokenKind {
+                fn id(self) -> usize {
+                    self as usize
+                }
+
+                fn from_id(id: usize) -> Option<Self> {
+                    Self::ALL.get(id).copied()
+                }
+            }
+
+            impl semasia_parser::Kinded for Token {
+                type Kind = TokenKind;
+
+                fn kind(&self) -> TokenKind {
+                    match self {
+                        #(Self::#tokens (_) => TokenKind::#tokens,)*
                     }
                 }
             }
@@ -103,13 +307,69 @@ impl<'a> Analyzed<'a> {
                         #(Self::#tokens (_) => #counter,)*
                     }
                 }
+
+                pub const fn display_name(&self) -> &'static str {
+                    match self {
+                        #(Self::#tokens (_) => #displays,)*
+                    }
+                }
+
+                #reinterpret
             }
         };
         file.items
     }
 
-    fn non_terminal_enum(&self) -> Vec<Item> {
-        let start_symbol = self.automaton.grammar().start_symbol().extras().id();
+    /// The lexer attributes of a token, the per-instance ones included. The patterns of a unit
+    /// struct get the callback that makes it.
+    pub(crate) fn token_attributes(
+        &self,
+        ident: &Ident,
+        instance: Option<usize>,
+    ) -> Vec<syn::Attribute> {
+        let token = self
+            .automaton
+            .grammar()
+            .tokens()
+            .iter()
+            .find(|token| token.extras().id() == ident)
+            .expect("token not found");
+        let instance_attributes = self
+            .automaton
+            .grammar()
+            .extras()
+            .generic
+            .iter()
+            .zip(instance)
+            .flat_map(move |(generic, instance)| {
+                generic
+                    .token_attributes
+                    .iter()
+                    .filter(move |(token, _)| token == ident)
+                    .flat_map(move |(_, attributes)| &attributes[instance])
+            });
+        let (attributes, _, _, _, value) = token.extras().extras();
+        attributes
+            .iter()
+            .chain(instance_attributes)
+            .cloned()
+            .map(|mut attribute| {
+                if *value == TokenValue::Unit
+                    && let Meta::List(raw_list) = &mut attribute.meta
+                {
+                    let parser =
+                        syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+                    if let Ok(mut list) = parser.parse2(raw_list.tokens.clone()) {
+                        list.insert(1, parse_quote!(|_| #ident {}));
+                        raw_list.tokens = quote!(#list);
+                    }
+                }
+                attribute
+            })
+            .collect()
+    }
+
+    fn non_terminal_enum(&self, derives: &[syn::Attribute]) -> Vec<Item> {
         let non_terminals = self
             .automaton
             .grammar()
@@ -117,24 +377,33 @@ impl<'a> Analyzed<'a> {
             .iter()
             .map(|non_terminal| non_terminal.extras().id())
             .collect_vec();
+        let types = non_terminals
+            .iter()
+            .map(|non_terminal| self.symbol_type(non_terminal));
+        let names = non_terminals
+            .iter()
+            .map(|non_terminal| self.item_name(non_terminal))
+            .collect_vec();
         let counter = 0usize..;
         let file: syn::File = parse_quote! {
+            #(#derives)*
+            #[allow(non_camel_case_types)]
             pub enum NonTerminal {
-                #(#non_terminals (#non_terminals),)*
+                #(#non_terminals (#types),)*
             }
 
-            impl std::fmt::Display for NonTerminal {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            impl core::fmt::Display for NonTerminal {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                     match self {
-                        #(Self::#non_terminals (_) => write!(f, stringify!(#non_terminals)),)*
+                        #(Self::#non_terminals (_) => write!(f, #names),)*
                     }
                 }
             }
 
-            impl std::fmt::Debug for NonTerminal {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            impl core::fmt::Debug for NonTerminal {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                     match self {
-                        #(Self::#non_terminals (_) => write!(f, stringify!(#non_terminals)),)*
+                        #(Self::#non_terminals (_) => write!(f, #names),)*
                     }
                 }
             }
@@ -146,15 +415,6 @@ impl<'a> Analyzed<'a> {
                     }
                 }
             }
-
-            impl From<NonTerminal> for #start_symbol {
-                fn from(value: NonTerminal) -> #start_symbol {
-                    match value {
-                        NonTerminal::#start_symbol(val) => val,
-                        _ => panic!(),
-                    }
-                }
-            }
         };
 
         file.items
@@ -167,17 +427,50 @@ impl<'a> Analyzed<'a> {
             .map(|prod| &prod.extras().0)
             .filter(|ident| ident != &"__SemasiaParse")
             .collect_vec();
+        let names = idents.iter().map(|ident| self.item_name(ident));
+        let Context {
+            sub_grammars,
+            predicates,
+            reclassify,
+            unit_conversions,
+            ..
+        } = self.automaton.grammar().extras();
+        // productions of a nested grammar without a context expect `()`
+        let without_ctx = |prod_name: &Ident| {
+            sub_grammars.iter().any(|sub_grammar| {
+                sub_grammar.compiler_ctx.is_none()
+                    && sub_grammar
+                        .productions
+                        .iter()
+                        .any(|production| &sub_grammar.local_name(production) == prod_name)
+            })
+        };
         let reductions = productions.iter().map(|prod| {
             let prod_name = &prod.extras().0;
+            let prod_type = self.symbol_type(prod_name);
+            let ctx = if without_ctx(prod_name) {
+                quote!(&mut ())
+            } else {
+                quote!(ctx)
+            };
             let head_type = prod.head().extras().id();
             let exprs = prod.body().iter().enumerate().map(|(i, sym)| {
                 let var_name = Ident::new(&format!("t{i}"), Span::call_site().into());
                 match sym {
                     SymbolicSymbol::Token(enriched_token) => {
                         let type_ident = enriched_token.extras().id();
-                        quote! {
-                            let Some(Symbol::Token(Token::#type_ident(#var_name))) = stacks.symbol_stack.pop() else { unreachable!("this is not a token") };
-                            stacks.state_stack.pop();
+                        // a token merged into another one is made again as the unit struct the production expects
+                        let conversion = unit_conversions.iter().find(|(production, position, _)| production == prod_name && *position == i);
+                        match conversion {
+                            Some((_, _, unit)) => quote! {
+                                let Some(Symbol::Token(Token::#type_ident(_))) = stacks.symbol_stack.pop() else { unreachable!("this is not a token") };
+                                stacks.state_stack.pop();
+                                let #var_name = #unit {};
+                            },
+                            None => quote! {
+                                let Some(Symbol::Token(Token::#type_ident(#var_name))) = stacks.symbol_stack.pop() else { unreachable!("this is not a token") };
+                                stacks.state_stack.pop();
+                            },
                         }
                     }
                     SymbolicSymbol::NonTerminal(enriched_non_terminal) => {
@@ -195,20 +488,52 @@ impl<'a> Analyzed<'a> {
                     #(#exprs)*
                         let body = (#(#vars),*);
 
-                    NonTerminal::#head_type(#prod_name::synthesize(ctx, body))
+                    NonTerminal::#head_type(<#prod_type>::synthesize(#ctx, body))
+                }
+            }
+        });
+        let predicate = (!predicates.is_empty()).then(|| {
+            let arms = predicates.iter().map(|(prod_name, closure)| {
+                let (ctx, ctx_type) = if without_ctx(prod_name) {
+                    (quote!(&()), quote!(()))
+                } else {
+                    (quote!(ctx), quote!(__CompilerContext))
+                };
+                quote! {
+                    Self::#prod_name => {
+                        let predicate: fn(&#ctx_type, Option<&Token>) -> bool = #closure;
+                        predicate(#ctx, lookahead)
+                    }
+                }
+            });
+            quote! {
+                fn predicate(&self, ctx: &__CompilerContext, lookahead: Option<&Token>) -> bool {
+                    match self {
+                        #(#arms,)*
+                        _ => true,
+                    }
+                }
+            }
+        });
+        let reclassify = reclassify.as_ref().map(|closure| {
+            quote! {
+                fn reclassify(ctx: &mut __CompilerContext, token: Token) -> Token {
+                    let reclassify: fn(&mut __CompilerContext, Token) -> Token = #closure;
+                    reclassify(ctx, token)
                 }
             }
         });
         let file: syn::File = parse_quote! {
             #[derive(Debug, Clone)]
+            #[allow(non_camel_case_types)]
             pub enum ProductionName {
                 #(#idents,)*
             }
 
-            impl std::fmt::Display for ProductionName {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            impl core::fmt::Display for ProductionName {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                     match self {
-                        #(Self::#idents => write!(f, stringify!(#idents)),)*
+                        #(Self::#idents => write!(f, #names),)*
                     }
                 }
             }
@@ -221,13 +546,17 @@ impl<'a> Analyzed<'a> {
                         #(Self::#idents => #reductions,)*
                     }
                 }
+
+                #predicate
+
+                #reclassify
             }
         };
         file.items
     }
 
     fn compiler_context(&self) -> Item {
-        let Context(compiler_ctx) = self.automaton.grammar().extras();
+        let Context { compiler_ctx, .. } = self.automaton.grammar().extras();
         compiler_ctx
             .as_ref()
             .map(|ctx| {
@@ -375,6 +704,57 @@ impl<'a> Analyzed<'a> {
                 quote!((#state, #token_id) => Some(#action))
             });
 
+        let production_name = |production: &usize| {
+            &self
+                .automaton
+                .grammar()
+                .productions()
+                .get(*production)
+                .expect("production not found")
+                .extras()
+                .0
+        };
+        let predicated_token_table_patts =
+            self.token_table
+                .predicated
+                .iter()
+                .map(|((state, token_id), productions)| {
+                    let productions = productions.iter().map(production_name);
+                    quote! {
+                        (#state, #token_id) => [#(ProductionName::#productions),*].into_iter().find(holds)
+                    }
+                });
+        let predicated_eof_table_patts =
+            self.eof_table
+                .predicated
+                .iter()
+                .map(|(state, productions)| {
+                    let productions = productions.iter().map(production_name);
+                    quote!(#state => [#(ProductionName::#productions),*].into_iter().find(holds))
+                });
+
+        let words = self.automaton.grammar().token_count().div_ceil(64).max(1);
+        let token_set_in_state_patts =
+            self.token_table
+                .table
+                .iter()
+                .enumerate()
+                .map(|(state, row)| {
+                    let mut bits = vec![0u64; words];
+                    for (token_id, _) in row.iter().enumerate().filter(|(token_id, opt_action)| {
+                        opt_action.is_some()
+                            || self
+                                .token_table
+                                .predicated
+                                .contains_key(&(state, *token_id))
+                    }) {
+                        bits[token_id / 64] |= 1 << (token_id % 64);
+                    }
+                    let end_of_input = self.eof_table.table[state].is_some()
+                        || self.eof_table.predicated.contains_key(&state);
+                    quote!(#state => TokenSet::from_bits([#(#bits),*], #end_of_input))
+                });
+
         let token_in_state_patts = self
             .token_table
             .table
@@ -386,27 +766,46 @@ impl<'a> Analyzed<'a> {
                     row.iter()
                         .enumerate()
                         .flat_map(move |(token_id, opt_action)| {
-                            opt_action.as_ref().map(|_| {
+                            (opt_action.is_some()
+                                || self.token_table.predicated.contains_key(&(state, token_id)))
+                            .then(|| {
                                 self.automaton
                                     .grammar()
                                     .tokens()
                                     .get(token_id)
                                     .unwrap()
                                     .extras()
-                                    .id()
-                                    .to_string()
+                                    .extras()
+                                    .3
+                                    .clone()
                             })
                         })
                         .chain(
-                            self.eof_table.table[state]
-                                .is_some()
-                                .then_some("__EndOfStream".into()),
+                            (self.eof_table.table[state].is_some()
+                                || self.eof_table.predicated.contains_key(&state))
+                            .then_some("end of input".into()),
                         )
                         .collect_vec(),
                 )
             })
             .map(|(state, tokens)| quote!(#state => &[#(#tokens),*]));
 
+        let non_terminals_in_state_patts = self
+            .automaton
+            .awaited_non_terminals()
+            .into_iter()
+            .enumerate()
+            .map(|(state, non_terminals)| {
+                let names = non_terminals.into_iter().map(|non_terminal| {
+                    self.item_name(
+                        self.automaton.grammar().non_terminals()[non_terminal]
+                            .extras()
+                            .id(),
+                    )
+                });
+                quote!(#state => &[#(#names),*])
+            });
+
         let eof_table_patts =
             self.eof_table
                 .table
@@ -447,11 +846,30 @@ impl<'a> Analyzed<'a> {
             .flatten()
             .map(|(state, token_id, target)| quote!((#state, #token_id) => Some(#target)));
 
+        let productions = self
+            .automaton
+            .grammar()
+            .productions()
+            .iter()
+            .filter(|prod| prod.extras().0 != "__SemasiaParse")
+            .collect_vec();
+        let production_idents = productions
+            .iter()
+            .map(|prod| &prod.extras().0)
+            .collect_vec();
+        let production_arities = productions.iter().map(|prod| prod.arity());
+        let production_heads = productions.iter().map(|prod| *prod.head().id());
+
+        let start_entry = Self::entry_marker(self.automaton.grammar().start_symbol().extras().id());
         let file: syn::File = parse_quote! {
             #[derive(Debug)]
-            pub struct Tables;
+            pub struct Tables<Entry = #start_entry>(core::marker::PhantomData<Entry>);
+
+            pub type TokenSet = semasia_parser::TokenSet<TokenKind, #words>;
+
+            impl<Entry> semasia_parser::Tables<NonTerminal, Token, ProductionName> for Tables<Entry> {
+                type TokenSet = TokenSet;
 
-            impl semasia_parser::Tables<NonTerminal, Token, ProductionName> for Tables {
                 fn query_token_table(current_state: usize, current_token: &Token) -> Option<semasia_parser::TokenAction<ProductionName>> {
                     match (current_state, current_token.id()) {
                         #(#token_table_patts,)*
@@ -464,26 +882,137 @@ impl<'a> Analyzed<'a> {
                         _ => None,
                     }
                 }
+                fn query_predicated_token_table(
+                    current_state: usize,
+                    current_token: &Token,
+                    holds: impl FnMut(&ProductionName) -> bool,
+                ) -> Option<ProductionName> {
+                    match (current_state, current_token.id()) {
+                        #(#predicated_token_table_patts,)*
+                        _ => None,
+                    }
+                }
+                fn query_predicated_eof_table(
+                    current_state: usize,
+                    holds: impl FnMut(&ProductionName) -> bool,
+                ) -> Option<ProductionName> {
+                    match current_state {
+                        #(#predicated_eof_table_patts,)*
+                        _ => None,
+                    }
+                }
+                fn reinterpret_token<'source>(
+                    current_token: &Token,
+                    source: &'source <Token as logos::Logos<'source>>::Source,
+                    span: core::ops::Range<usize>,
+                    accepts: impl FnMut(&Token) -> bool,
+                ) -> Option<Token>
+                where
+                    Token: logos::Logos<'source>,
+                {
+                    current_token.reinterpret(source, span, accepts)
+                }
                 fn query_goto_table(current_state: usize, non_terminal: &NonTerminal) -> Option<usize> {
-                    match (current_state, non_terminal.id()) {
+                    Self::query_goto_table_by_id(current_state, non_terminal.id())
+                }
+                fn query_goto_table_by_id(current_state: usize, non_terminal_id: usize) -> Option<usize> {
+                    match (current_state, non_terminal_id) {
                         #(#non_terminal_patts,)*
                         _ => None,
                     }
                 }
+                fn production_arity(production: &ProductionName) -> usize {
+                    match production {
+                        #(ProductionName::#production_idents => #production_arities,)*
+                    }
+                }
+                fn production_head(production: &ProductionName) -> usize {
+                    match production {
+                        #(ProductionName::#production_idents => #production_heads,)*
+                    }
+                }
                 fn tokens_in_state(state: usize) -> &'static[&'static str] {
                     match state {
                         #(#token_in_state_patts,)*
                         _ => &[]
                     }
                 }
+                fn token_set_in_state(state: usize) -> TokenSet {
+                    match state {
+                        #(#token_set_in_state_patts,)*
+                        _ => TokenSet::new()
+                    }
+                }
+                fn non_terminals_in_state(state: usize) -> &'static [&'static str] {
+                    match state {
+                        #(#non_terminals_in_state_patts,)*
+                        _ => &[]
+                    }
+                }
             }
         };
 
         file.items
     }
 
-    fn parser(&self) -> Item {
-        let start_symbol = self.automaton.grammar().start_symbol().extras().id();
-        parse_quote!(pub type Parser = semasia_parser::Parser<NonTerminal, Token, #start_symbol, ProductionName, Tables, __CompilerContext>;)
+    fn entry_marker(non_terminal: &Ident) -> Ident {
+        format_ident!("__{}Entry", non_terminal)
+    }
+
+    fn entry_points(&self) -> Vec<Item> {
+        self.automaton
+            .entry_points()
+            .into_iter()
+            .flat_map(|(initial_state, non_terminal)| {
+                let non_terminal = non_terminal.extras().id();
+                let ty = self.symbol_type(non_terminal);
+                let marker = Self::entry_marker(non_terminal);
+                let file: syn::File = parse_quote! {
+                    #[doc(hidden)]
+                    #[derive(Debug)]
+                    pub struct #marker;
+
+                    impl semasia_parser::EntryPoint<NonTerminal, #ty> for Tables<#marker> {
+                        const INITIAL_STATE: usize = #initial_state;
+
+                        #[allow(unreachable_patterns)]
+                        fn accept(non_terminal: NonTerminal) -> #ty {
+                            match non_terminal {
+                                NonTerminal::#non_terminal(value) => value,
+                                _ => unreachable!("accepted a different non terminal"),
+                            }
+                        }
+                    }
+                };
+                file.items
+            })
+            .collect()
+    }
+
+    fn parser(&self) -> Vec<Item> {
+        let start_symbol = self.symbol_type(self.automaton.grammar().start_symbol().extras().id());
+        let entry_points = self
+            .automaton
+            .grammar()
+            .entry_points()
+            .map(|non_terminal| non_terminal.extras().id())
+            .collect_vec();
+        let entry_types = entry_points
+            .iter()
+            .map(|non_terminal| self.symbol_type(non_terminal));
+        let entry_parsers = entry_points
+            .iter()
+            .map(|non_terminal| format_ident!("{}Parser", non_terminal));
+        let entry_markers = entry_points
+            .iter()
+            .map(|non_terminal| Self::entry_marker(non_terminal));
+        let file: syn::File = parse_quote! {
+            pub type Parser = semasia_parser::Parser<NonTerminal, Token, #start_symbol, ProductionName, Tables, __CompilerContext>;
+
+            pub type ObservedParser<Obs> = semasia_parser::Parser<NonTerminal, Token, #start_symbol, ProductionName, Tables, __CompilerContext, Obs>;
+
+            #(pub type #entry_parsers = semasia_parser::Parser<NonTerminal, Token, #entry_types, ProductionName, Tables<#entry_markers>, __CompilerContext>;)*
+        };
+        file.items
     }
 }
@@ -1,17 +1,24 @@
 use crate::constructor::Constructor;
 use proc_macro::TokenStream;
-use proc_macro_error::{abort_call_site, abort_if_dirty, proc_macro_error, set_dummy};
+use proc_macro_error::{abort, abort_call_site, abort_if_dirty, proc_macro_error, set_dummy};
 use quote::quote;
-use syn::{File, Ident, ItemMod};
+use std::path::PathBuf;
+use syn::{
+    AttrStyle, Attribute, Expr, ExprLit, File, Ident, ItemMod, Lit, LitStr, Meta, Token,
+    parse::Parser, parse_quote, punctuated::Punctuated,
+};
 
 mod constructor;
+mod contextual_lexing;
+mod generic_grammar;
 mod grammar_extraction;
 mod item_injections;
+mod named_bindings;
 
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn grammar(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let internal_mod_name = syn::parse::<Ident>(attr).ok();
+    let (internal_mod_name, path) = grammar_arguments(attr.into());
     if let Ok(mut module) = syn::parse::<ItemMod>(item.clone()) {
         let ident = &module.ident;
         set_dummy(quote! {
@@ -19,6 +26,9 @@ pub fn grammar(attr: TokenStream, item: TokenStream) -> TokenStream {
                 pub type Parser = semasia_parser::dummy::DummyParser;
             }
         });
+        if let Some(path) = path {
+            load_module_file(&mut module, &path);
+        }
         let (_, items) = module
             .content
             .as_mut()
@@ -49,6 +59,92 @@ pub fn grammar(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// `#[grammar]` takes the name of the internal module and the `path` of the file holding the
+/// grammar, both optional: `#[grammar(internal, path = "grammar.rs")]`.
+fn grammar_arguments(attr: proc_macro2::TokenStream) -> (Option<Ident>, Option<LitStr>) {
+    let arguments = Punctuated::<Meta, Token![,]>::parse_terminated
+        .parse2(attr)
+        .unwrap_or_else(|error| abort!(error.span(), error));
+    let mut internal_mod_name = None;
+    let mut path = None;
+    for argument in arguments {
+        match argument {
+            Meta::Path(name) if name.get_ident().is_some() => {
+                internal_mod_name = name.get_ident().cloned()
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("path") => {
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) = name_value.value
+                else {
+                    abort!(
+                        name_value.value,
+                        "the path of a grammar has to be a string literal"
+                    );
+                };
+                path = Some(lit);
+            }
+            argument => abort!(
+                argument, "unexpected grammar argument";
+                help = "use `#[grammar(internal_module_name, path = \"file.rs\")]`"
+            ),
+        }
+    }
+    (internal_mod_name, path)
+}
+
+/// Reads the body of `mod name {}` from a file, relative to the directory of the file that
+/// declares the module like `#[path]` is. The file is included in the output so that changing
+/// it rebuilds the grammar.
+fn load_module_file(module: &mut ItemMod, path: &LitStr) {
+    if module
+        .content
+        .as_ref()
+        .is_some_and(|(_, items)| !items.is_empty())
+    {
+        abort!(
+            module.ident, "a grammar loaded from a file has to be an empty module";
+            help = "write `mod {} {{}}`", module.ident
+        );
+    }
+    let directory = proc_macro::Span::call_site()
+        .local_file()
+        .and_then(|file| file.parent().map(PathBuf::from))
+        .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+        .unwrap_or_default();
+    let file_path = directory.join(path.value());
+    let source = std::fs::read_to_string(&file_path)
+        .unwrap_or_else(|error| abort!(path, "couldn't read `{}`: {}", file_path.display(), error));
+    let File {
+        attrs, mut items, ..
+    } = syn::parse_file(&source).unwrap_or_else(|error| {
+        abort!(path, "couldn't parse `{}`: {}", file_path.display(), error)
+    });
+    // the inner attributes of the file become attributes of the module
+    module.attrs.extend(
+        attrs
+            .into_iter()
+            .filter(|attr| {
+                attr.path()
+                    .segments
+                    .last()
+                    .is_none_or(|segment| segment.ident != "grammar")
+            })
+            .map(|attr| Attribute {
+                style: AttrStyle::Outer,
+                ..attr
+            }),
+    );
+    // `include_str!` resolves relative paths from the declaring file, not the working directory
+    let file_path = std::fs::canonicalize(&file_path).unwrap_or(file_path);
+    let file_path = file_path.to_string_lossy();
+    items.push(parse_quote! {
+        const _: &str = include_str!(#file_path);
+    });
+    module.content = Some((Default::default(), items));
+    module.semi = None;
+}
+
 macro_rules! dummy_attribute {
     ($attr:ident, $pos:expr) => {
         #[proc_macro_error]
@@ -65,6 +161,7 @@ dummy_attribute!(
     start_symbol,
     "type aliases, structs, enums or use directives"
 );
+dummy_attribute!(entry_point, "non-terminals");
 dummy_attribute!(
     non_terminal,
     "type aliases, structs, enums or use directives"
@@ -72,6 +169,7 @@ dummy_attribute!(
 dummy_attribute!(left_associative, "tokens");
 dummy_attribute!(right_associative, "tokens");
 dummy_attribute!(priority, "tokens or productions");
+dummy_attribute!(display, "tokens");
 dummy_attribute!(
     context,
     "ONLY ONE type alias, struct, enum or use directive"
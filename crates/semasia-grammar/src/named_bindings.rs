@@ -0,0 +1,251 @@
+use itertools::Itertools;
+use proc_macro_error::emit_error;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Block, ExprClosure, Generics, Ident, Item, Pat, PatIdent, PatType, Stmt, Token, Type,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token::Brace,
+};
+
+use crate::constructor::Constructor;
+
+/// An element of a production body, optionally bound to a name: `lhs: Expr`.
+struct BodyElement {
+    name: Option<Ident>,
+    value: ElementValue,
+}
+
+enum ElementValue {
+    Symbol(Type),
+    /// A mid-rule action, `@{ ... }` or `@Type { ... }` when it has a value.
+    Action(Option<Type>, Block),
+}
+
+impl Parse for BodyElement {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let name = input.parse()?;
+            input.parse::<Token![:]>()?;
+            Some(name)
+        } else {
+            None
+        };
+        let value = if input.parse::<Option<Token![@]>>()?.is_some() {
+            let ty = if input.peek(Brace) {
+                None
+            } else {
+                Some(input.parse()?)
+            };
+            ElementValue::Action(ty, input.parse()?)
+        } else {
+            ElementValue::Symbol(input.parse()?)
+        };
+        Ok(Self { name, value })
+    }
+}
+
+struct NamedProduction {
+    name: Ident,
+    generics: Generics,
+    head: Type,
+    body: Vec<BodyElement>,
+    action: Option<ExprClosure>,
+}
+
+impl Parse for NamedProduction {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let generics = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let head = input.parse()?;
+        input.parse::<Token![->]>()?;
+        let content;
+        parenthesized!(content in input);
+        let body = Punctuated::<BodyElement, Token![,]>::parse_terminated(&content)?
+            .into_iter()
+            .collect();
+        let action = if input.is_empty() {
+            None
+        } else {
+            input.parse::<Token![,]>()?;
+            Some(input.parse()?)
+        };
+        Ok(Self {
+            name,
+            generics,
+            head,
+            body,
+            action,
+        })
+    }
+}
+
+impl Constructor {
+    /// Rewrites `production!(P: A -> (lhs: B, C, rhs: D), |lhs, rhs| ...)` into the positional
+    /// form, each parameter of the semantic action takes the place of the element with its name,
+    /// whatever their order, and the other elements are ignored. Mid-rule actions become empty non-terminals, which are
+    /// returned along with their productions.
+    pub(crate) fn desugar_production(item: &mut Item) -> Vec<Item> {
+        let Item::Macro(mac) = item else {
+            return Vec::new();
+        };
+        if !mac.mac.path.is_ident("production") {
+            return Vec::new();
+        }
+        let Ok(production) = mac.mac.parse_body::<NamedProduction>() else {
+            return Vec::new();
+        };
+        if production.body.iter().all(|element| {
+            element.name.is_none() && matches!(element.value, ElementValue::Symbol(_))
+        }) {
+            return Vec::new();
+        }
+        let NamedProduction {
+            name,
+            generics,
+            head,
+            body,
+            action,
+        } = production;
+        let mut helpers = Vec::new();
+        let types = body
+            .iter()
+            .enumerate()
+            .map(|(position, element)| match &element.value {
+                ElementValue::Symbol(ty) => ty.to_token_stream(),
+                ElementValue::Action(ty, block) => {
+                    let (helper, items) =
+                        Self::mid_rule_action(&name, position, ty.as_ref(), block);
+                    helpers.extend(items);
+                    helper.to_token_stream()
+                }
+            })
+            .collect_vec();
+        let body_type = match types.as_slice() {
+            [ty] => quote!(#ty),
+            types => quote!((#(#types),*)),
+        };
+        mac.mac.tokens = quote!(#name #generics: #head -> #body_type);
+        let Some(action) = action else {
+            return helpers;
+        };
+
+        let names = body
+            .iter()
+            .filter_map(|element| element.name.as_ref())
+            .collect_vec();
+        if names.is_empty() {
+            // a positional action already takes the whole body, helpers included
+            mac.mac.tokens = quote!(#name #generics: #head -> #body_type, #action);
+            return helpers;
+        }
+        let mut inputs = action.inputs.iter().cloned().collect_vec();
+        let ctx = match inputs.len().checked_sub(names.len()) {
+            Some(0) => None,
+            Some(1) => Some(inputs.remove(0)),
+            _ => {
+                emit_error!(
+                    action, "the semantic action has to take one parameter for each named element";
+                    note = "the named elements are `{}`", names.iter().format("`, `");
+                    help = "the compilation context can be taken as an extra first parameter"
+                );
+                return helpers;
+            }
+        };
+        let mut bindings = Vec::new();
+        for input in inputs {
+            // type annotations aren't allowed inside the tuple pattern
+            let input = match input {
+                Pat::Type(PatType { pat, .. }) => *pat,
+                input => input,
+            };
+            let Pat::Ident(binding) = input else {
+                emit_error!(
+                    input, "the parameters of the semantic action have to be the names of the elements";
+                    note = "the named elements are `{}`", names.iter().format("`, `")
+                );
+                return helpers;
+            };
+            if !names.contains(&&binding.ident) {
+                emit_error!(
+                    binding.ident, "`{}` isn't the name of an element of the body", binding.ident;
+                    note = "the named elements are `{}`", names.iter().format("`, `")
+                );
+                return helpers;
+            }
+            if bindings
+                .iter()
+                .any(|other: &PatIdent| other.ident == binding.ident)
+            {
+                emit_error!(binding.ident, "`{}` is bound more than once", binding.ident);
+                return helpers;
+            }
+            bindings.push(binding);
+        }
+        let patterns = body
+            .iter()
+            .map(|element| {
+                match bindings
+                    .iter()
+                    .find(|binding| Some(&binding.ident) == element.name.as_ref())
+                {
+                    Some(binding) => Pat::Ident(binding.clone()),
+                    None => syn::parse_quote!(_),
+                }
+            })
+            .collect_vec();
+        let pattern = match patterns.as_slice() {
+            [pattern] => quote!(#pattern),
+            patterns => quote!((#(#patterns),*)),
+        };
+        let action_body = &action.body;
+        let ctx = ctx.map(|ctx| quote!(#ctx,));
+        mac.mac.tokens = quote!(#name #generics: #head -> #body_type, |#ctx #pattern| #action_body);
+        helpers
+    }
+
+    /// The empty non-terminal that runs a mid-rule action when it's reduced, the action sees the
+    /// compilation context as `ctx`. Conflicts it introduces are reported on the action.
+    fn mid_rule_action(
+        production: &Ident,
+        position: usize,
+        ty: Option<&Type>,
+        block: &Block,
+    ) -> (Ident, Vec<Item>) {
+        let span = block.brace_token.span.join();
+        let helper = format_ident!("__{}Action{}", production, position, span = span);
+        let helper_production =
+            format_ident!("__{}Action{}Reduce", production, position, span = span);
+        let ty = ty.map_or_else(|| quote!(()), ToTokens::to_token_stream);
+        let ctx = Ident::new("ctx", Span::call_site());
+        let parameters = if mentions(block.to_token_stream(), &ctx) {
+            quote!(#ctx, _)
+        } else {
+            quote!(_)
+        };
+        // a lone expression isn't wrapped in braces again by `production!`
+        let action = match block.stmts.as_slice() {
+            [Stmt::Expr(expr, None)] => expr.to_token_stream(),
+            _ => block.to_token_stream(),
+        };
+        let file: syn::File = syn::parse_quote! {
+            #[doc(hidden)]
+            #[non_terminal]
+            pub type #helper = #ty;
+
+            production!(#helper_production: #helper -> (), |#parameters| #action);
+        };
+        (helper, file.items)
+    }
+}
+
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        TokenTree::Ident(other) => &other == ident,
+        TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
@@ -0,0 +1,69 @@
+use alloc::vec::Vec;
+use core::{error::Error, fmt::Display, ops::Range};
+
+use crate::{Stacks, Symbol};
+
+#[derive(Debug, Clone)]
+pub struct Checkpoint<NonTerminal, Token, Ctx> {
+    pub(crate) stacks: Stacks<NonTerminal, Token>,
+    pub(crate) ctx: Option<Ctx>,
+    pub(crate) actions: usize,
+    pub(crate) marks: Vec<MarkEntry>,
+    pub(crate) undo_log: Vec<UndoEntry<NonTerminal, Token>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mark {
+    pub(crate) generation: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MarkEntry {
+    pub(crate) generation: usize,
+    pub(crate) symbol_height: usize,
+    pub(crate) actions: usize,
+    /// Where the undo log was when the mark was taken.
+    pub(crate) log_start: usize,
+}
+
+/// A symbol popped by a reduction while a mark above it was alive, with the state it was pushed
+/// with.
+#[derive(Debug, Clone)]
+pub(crate) struct UndoEntry<NonTerminal, Token> {
+    pub(crate) position: usize,
+    pub(crate) state: usize,
+    pub(crate) symbol: Symbol<NonTerminal, Token>,
+}
+
+pub(crate) type CloneEntries<NonTerminal, Token> =
+    fn(&Stacks<NonTerminal, Token>, Range<usize>) -> Vec<UndoEntry<NonTerminal, Token>>;
+
+/// Taken by `mark`, where the symbols are known to be `Clone`, so that reductions can log the
+/// symbols they are about to consume.
+pub(crate) fn clone_entries<NonTerminal: Clone, Token: Clone>(
+    stacks: &Stacks<NonTerminal, Token>,
+    positions: Range<usize>,
+) -> Vec<UndoEntry<NonTerminal, Token>> {
+    positions
+        .map(|position| UndoEntry {
+            position,
+            state: stacks.state_stack[position + 1],
+            symbol: stacks.symbol_stack[position].clone(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollbackError {
+    MarkReleased,
+}
+
+impl Display for RollbackError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RollbackError::MarkReleased => write!(f, "mark was already released"),
+        }
+    }
+}
+
+impl Error for RollbackError {}
@@ -0,0 +1,272 @@
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    error::Error,
+    fmt::{self, Display, Write},
+    ops::Range,
+};
+
+use crate::{Location, RenderMode, SourceMap, style::Style};
+
+pub trait ToDiagnostic<'source> {
+    fn to_diagnostic(&self) -> Diagnostic<'source>;
+
+    fn render(&self, render_mode: RenderMode) -> String {
+        let mut rendered = String::new();
+        self.to_diagnostic()
+            .render_with(&mut rendered, render_mode)
+            .expect("writing to a string cannot fail");
+        rendered
+    }
+
+    fn to_structured(&self) -> StructuredDiagnostic {
+        self.to_diagnostic().to_structured()
+    }
+
+    #[cfg(feature = "codespan-reporting")]
+    fn to_codespan<FileId>(
+        &self,
+        file_id: FileId,
+    ) -> codespan_reporting::diagnostic::Diagnostic<FileId> {
+        self.to_diagnostic().to_codespan(file_id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic<'source> {
+    pub code: &'static str,
+    pub message: String,
+    pub label: String,
+    pub span: Range<usize>,
+    pub found: Option<String>,
+    pub expected: Vec<String>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    pub source: Cow<'source, str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StructuredDiagnostic {
+    pub kind: &'static str,
+    pub message: String,
+    pub span: Range<usize>,
+    pub start: Location,
+    pub end: Location,
+    pub found: Option<String>,
+    pub expected: Vec<String>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl<'source> Diagnostic<'source> {
+    pub fn new(
+        code: &'static str,
+        message: impl ToString,
+        label: impl ToString,
+        span: Range<usize>,
+        source: impl Into<Cow<'source, str>>,
+    ) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            label: label.to_string(),
+            span,
+            found: None,
+            expected: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+            source: source.into(),
+        }
+    }
+
+    pub fn with_found(mut self, found: impl ToString) -> Self {
+        self.found = Some(found.to_string());
+        self
+    }
+
+    pub fn with_expected<T: ToString>(mut self, expected: impl IntoIterator<Item = T>) -> Self {
+        self.expected = expected
+            .into_iter()
+            .map(|token| token.to_string())
+            .collect();
+        self
+    }
+
+    pub fn with_note(mut self, note: impl ToString) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl ToString) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn into_owned(self) -> Diagnostic<'static> {
+        Diagnostic {
+            code: self.code,
+            message: self.message,
+            label: self.label,
+            span: self.span,
+            found: self.found,
+            expected: self.expected,
+            notes: self.notes,
+            help: self.help,
+            source: Cow::Owned(self.source.into_owned()),
+        }
+    }
+
+    pub fn render<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        self.render_with(w, RenderMode::Auto)
+    }
+
+    pub fn render_with<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        render_mode: RenderMode,
+    ) -> fmt::Result {
+        let source_map = SourceMap::new(&self.source).render_mode(render_mode);
+        let colored = render_mode.is_colored();
+        writeln!(
+            w,
+            "{}{}",
+            Style::Error.paint("error", colored),
+            Style::Bold.paint(format_args!(": {}", self.message), colored)
+        )?;
+        source_map.write_snippet(w, self.span.clone())?;
+        for note in &self.notes {
+            writeln!(w)?;
+            source_map.write_note(w, self.span.clone(), note)?;
+        }
+        if let Some(help) = &self.help {
+            writeln!(w)?;
+            source_map.write_help(w, self.span.clone(), help)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_structured(&self) -> StructuredDiagnostic {
+        let source_map = SourceMap::new(&self.source);
+        StructuredDiagnostic {
+            kind: self.code,
+            message: self.message.clone(),
+            span: self.span.clone(),
+            start: source_map.location(self.span.start),
+            end: source_map.location(self.span.end),
+            found: self.found.clone(),
+            expected: self.expected.clone(),
+            notes: self.notes.clone(),
+            help: self.help.clone(),
+        }
+    }
+
+    #[cfg(feature = "codespan-reporting")]
+    pub fn to_codespan<FileId>(
+        &self,
+        file_id: FileId,
+    ) -> codespan_reporting::diagnostic::Diagnostic<FileId> {
+        use codespan_reporting::diagnostic::Label;
+
+        codespan_reporting::diagnostic::Diagnostic::error()
+            .with_code(self.code)
+            .with_message(&self.message)
+            .with_label(Label::primary(file_id, self.span.clone()).with_message(&self.label))
+            .with_notes_iter(
+                self.notes
+                    .iter()
+                    .cloned()
+                    .chain(self.help.iter().map(|help| alloc::format!("help: {help}"))),
+            )
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Diagnostic<'_> {}
+
+#[cfg(feature = "miette")]
+impl Diagnostic<'_> {
+    /// The notes and the help, miette has a single help text.
+    pub(crate) fn miette_help(&self) -> Option<String> {
+        if self.notes.is_empty() && self.help.is_none() {
+            return None;
+        }
+        let help = self
+            .notes
+            .iter()
+            .chain(self.help.iter())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(help)
+    }
+
+    pub(crate) fn miette_label(&self) -> miette::LabeledSpan {
+        let mut span = self.span.clone();
+        if span.is_empty() && span.start >= self.source.len() {
+            // miette does not draw labels past the end of the source, point at its last character
+            if let Some((index, last)) = self.source.char_indices().last() {
+                span = index..index + last.len_utf8();
+            }
+        }
+        miette::LabeledSpan::new_primary_with_span(Some(self.label.clone()), span)
+    }
+}
+
+/// Implements `miette::Diagnostic` for an error that holds its `source`, from its
+/// `to_diagnostic`.
+#[cfg(feature = "miette")]
+macro_rules! miette_diagnostic_from_to_diagnostic {
+    () => {
+        fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new(self.to_diagnostic().code))
+        }
+
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            self.to_diagnostic()
+                .miette_help()
+                .map(|help| Box::new(help) as Box<dyn Display>)
+        }
+
+        fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+            Some(&self.source)
+        }
+
+        fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+            Some(Box::new(core::iter::once(
+                self.to_diagnostic().miette_label(),
+            )))
+        }
+    };
+}
+
+#[cfg(feature = "miette")]
+pub(crate) use miette_diagnostic_from_to_diagnostic;
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Diagnostic<'_> {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.miette_help()
+            .map(|help| Box::new(help) as Box<dyn Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(core::iter::once(self.miette_label())))
+    }
+}
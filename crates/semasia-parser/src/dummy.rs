@@ -1,14 +1,14 @@
-use std::fmt::Display;
+use core::fmt::Display;
 
 use logos::Logos;
 
-use crate::{EofAction, Parser, Reduce, Stacks, Tables, TokenAction};
+use crate::{EntryPoint, EofAction, Parser, Reduce, Stacks, Tables, TokenAction};
 
 #[derive(Debug)]
 pub struct DummyNonTerminal;
 
 impl Display for DummyNonTerminal {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         unreachable!()
     }
 }
@@ -17,7 +17,7 @@ impl Display for DummyNonTerminal {
 pub enum DummyToken {}
 
 impl Display for DummyToken {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         unreachable!()
     }
 }
@@ -26,13 +26,7 @@ impl Display for DummyToken {
 pub struct DummyStartSymbol;
 
 impl Display for DummyStartSymbol {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        unreachable!()
-    }
-}
-
-impl From<DummyNonTerminal> for DummyStartSymbol {
-    fn from(_value: DummyNonTerminal) -> Self {
+    fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         unreachable!()
     }
 }
@@ -47,6 +41,8 @@ pub type DummyParser =
     Parser<DummyNonTerminal, DummyToken, DummyStartSymbol, DummyProductions, DummyTable, ()>;
 
 impl Tables<DummyNonTerminal, DummyToken, DummyProductions> for DummyTable {
+    type TokenSet = ();
+
     fn query_token_table(
         _current_state: usize,
         _current_token: &DummyToken,
@@ -62,9 +58,37 @@ impl Tables<DummyNonTerminal, DummyToken, DummyProductions> for DummyTable {
         unreachable!()
     }
 
+    fn query_goto_table_by_id(_current_state: usize, _non_terminal_id: usize) -> Option<usize> {
+        unreachable!()
+    }
+
+    fn production_arity(_production: &DummyProductions) -> usize {
+        unreachable!()
+    }
+
+    fn production_head(_production: &DummyProductions) -> usize {
+        unreachable!()
+    }
+
     fn tokens_in_state(_current_state: usize) -> &'static [&'static str] {
         unreachable!()
     }
+
+    fn token_set_in_state(_current_state: usize) -> Self::TokenSet {
+        unreachable!()
+    }
+
+    fn non_terminals_in_state(_current_state: usize) -> &'static [&'static str] {
+        unreachable!()
+    }
+}
+
+impl EntryPoint<DummyNonTerminal, DummyStartSymbol> for DummyTable {
+    const INITIAL_STATE: usize = 0;
+
+    fn accept(_non_terminal: DummyNonTerminal) -> DummyStartSymbol {
+        unreachable!()
+    }
 }
 
 impl Reduce<DummyNonTerminal, DummyToken, ()> for DummyProductions {
@@ -1,30 +1,54 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::result_large_err)]
+
+#[doc(hidden)]
+pub extern crate alloc;
+
 use crate::results::{
-    LexError, LexParseError, ParseEof, ParseEofError, ParseEofErrorReason, ParseError,
-    ParseOneError, ParseToken, ParseTokenError, ParseTokenErrorReason,
+    Completions, InputStatus, LexError, LexParseError, LimitExceededError, ParseEof, ParseEofError,
+    ParseEofErrorReason, ParseError, ParseOneError, ParseToken, ParseTokenError,
+    ParseTokenErrorReason, Recovered,
 };
-use logos::Logos;
-use std::{
+use alloc::vec::Vec;
+use core::{
     fmt::{Debug, Display},
     marker::PhantomData,
     ops::Range,
 };
+use logos::{Logos, Source};
 
 mod actions;
+mod checkpoint;
+mod diagnostic;
 pub mod dummy;
+mod limits;
+mod observer;
+mod recovery;
 pub mod results;
+mod source_map;
+mod style;
+mod token_set;
 mod traits;
 
 pub use actions::*;
+pub use checkpoint::*;
+pub use diagnostic::*;
+pub use limits::*;
+pub use observer::*;
+pub use recovery::*;
+pub use source_map::*;
+pub use style::RenderMode;
+pub use token_set::*;
 pub use traits::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Symbol<NonTerminal, Token> {
     NonTerminal(NonTerminal),
     Token(Token),
 }
 
 impl<NonTerminal: Display, Token: Display> Display for Symbol<NonTerminal, Token> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Symbol::NonTerminal(nt) => write!(f, "NonTerminal({nt})"),
             Symbol::Token(tok) => write!(f, "Token({tok})"),
@@ -32,7 +56,7 @@ impl<NonTerminal: Display, Token: Display> Display for Symbol<NonTerminal, Token
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Stacks<NonTerminal, Token> {
     pub state_stack: Vec<usize>,
     pub symbol_stack: Vec<Symbol<NonTerminal, Token>>,
@@ -41,11 +65,42 @@ pub struct Stacks<NonTerminal, Token> {
 impl<NonTerminal, Token> Stacks<NonTerminal, Token> {
     pub fn new() -> Self {
         Self {
-            state_stack: vec![0],
+            state_stack: alloc::vec![0],
             symbol_stack: Vec::new(),
         }
     }
 
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut stacks = Self {
+            state_stack: Vec::with_capacity(capacity + 1),
+            symbol_stack: Vec::with_capacity(capacity),
+        };
+        stacks.state_stack.push(0);
+        stacks
+    }
+
+    pub fn with_initial_state(initial_state: usize) -> Self {
+        Self {
+            state_stack: alloc::vec![initial_state],
+            symbol_stack: Vec::new(),
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.state_stack.reserve(additional);
+        self.symbol_stack.reserve(additional);
+    }
+
+    pub fn reset(&mut self) {
+        self.reset_to(0);
+    }
+
+    pub fn reset_to(&mut self, initial_state: usize) {
+        self.state_stack.clear();
+        self.state_stack.push(initial_state);
+        self.symbol_stack.clear();
+    }
+
     pub fn current_state(&self) -> usize {
         *self.state_stack.last().expect("state stack is empty!")
     }
@@ -70,22 +125,37 @@ impl<NonTerminal, Terminal> Default for Stacks<NonTerminal, Terminal> {
     }
 }
 
-pub struct Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx> {
+pub struct Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs = NoObserver> {
     stacks: Stacks<NonTerminal, Token>,
     ctx: Ctx,
+    observer: Obs,
+    limits: ParseLimits,
+    actions: usize,
+    marks: Vec<MarkEntry>,
+    next_mark: usize,
+    undo_log: Vec<UndoEntry<NonTerminal, Token>>,
+    clone_entries: Option<CloneEntries<NonTerminal, Token>>,
+    contextual_lexing: bool,
+    /// The position of the input being consumed, which a resumed parse starts from.
+    offset: usize,
     phantom_data: PhantomData<(StartSymbol, Prod, Tab)>,
 }
 
-impl<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx> Debug
-    for Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx>
+impl<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs> Debug
+    for Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>
 where
     Stacks<NonTerminal, Token>: Debug,
     Ctx: Debug,
+    Obs: Debug,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Parser")
             .field("stacks", &self.stacks)
             .field("ctx", &self.ctx)
+            .field("observer", &self.observer)
+            .field("limits", &self.limits)
+            .field("actions", &self.actions)
+            .field("contextual_lexing", &self.contextual_lexing)
             .finish()
     }
 }
@@ -109,26 +179,131 @@ pub type LexParseResult<'source, Parser, NonTerminal, Token, ReturnType> = Resul
     >,
 >;
 
+pub type LexParseStatus<'source, Parser, NonTerminal, Token, Expected, ReturnType> = InputStatus<
+    ReturnType,
+    Expected,
+    LexParseError<
+        LexError<'source, Parser, Token>,
+        ParseError<
+            Parser,
+            NonTerminal,
+            Token,
+            Range<usize>,
+            &'source <Token as Logos<'source>>::Source,
+        >,
+    >,
+>;
+
+pub type RecoveringLexParseResult<'source, Parser, NonTerminal, Token, ReturnType> = Recovered<
+    Result<
+        ReturnType,
+        ParseError<
+            Parser,
+            NonTerminal,
+            Token,
+            Range<usize>,
+            &'source <Token as Logos<'source>>::Source,
+        >,
+    >,
+    LexError<'source, (), Token>,
+>;
+
+type ResumeResult<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs> = ParseResult<
+    Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>,
+    NonTerminal,
+    Token,
+    (StartSymbol, Ctx),
+>;
+
+type ResumeLexResult<'source, NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs> =
+    LexParseResult<
+        'source,
+        Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>,
+        NonTerminal,
+        Token,
+        (StartSymbol, Ctx),
+    >;
+
+type ConsumeSourceResult<NonTerminal, Token, LexerError, ReturnType> = Result<
+    ReturnType,
+    LexParseError<(LexerError, Range<usize>), ParseOneError<NonTerminal, Token, Range<usize>>>,
+>;
+
 impl<
     NonTerminal,
     Token,
-    StartSymbol: From<NonTerminal>,
+    StartSymbol,
     Prod: Reduce<NonTerminal, Token, Ctx>,
-    Tab: Tables<NonTerminal, Token, Prod>,
+    Tab: Tables<NonTerminal, Token, Prod> + EntryPoint<NonTerminal, StartSymbol>,
     Ctx,
-> Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx>
+    Obs: ParseObserver<NonTerminal, Token, Prod>,
+> Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>
 {
-    pub fn with_ctx(ctx: Ctx) -> Self {
+    pub fn with_ctx(ctx: Ctx) -> Self
+    where
+        Obs: Default,
+    {
+        Self::with_ctx_and_observer(ctx, Default::default())
+    }
+
+    pub fn with_ctx_and_observer(ctx: Ctx, observer: Obs) -> Self {
         Self {
-            stacks: Stacks::new(),
+            stacks: Stacks::with_initial_state(Tab::INITIAL_STATE),
             ctx,
+            observer,
+            limits: ParseLimits::default(),
+            actions: 0,
+            marks: Vec::new(),
+            next_mark: 0,
+            undo_log: Vec::new(),
+            clone_entries: None,
+            contextual_lexing: false,
+            offset: 0,
             phantom_data: PhantomData,
         }
     }
 
+    pub fn with_limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.stacks.reserve(capacity);
+        self
+    }
+
+    /// When the current state has no action for a lexed token, lexes its text as another token
+    /// that the state accepts, if there is one.
+    pub fn with_contextual_lexing(mut self, contextual_lexing: bool) -> Self {
+        self.contextual_lexing = contextual_lexing;
+        self
+    }
+
+    pub fn reset(&mut self) {
+        self.stacks.reset_to(Tab::INITIAL_STATE);
+        self.actions = 0;
+        self.marks.clear();
+        self.undo_log.clear();
+        self.offset = 0;
+    }
+
+    pub fn ctx(&self) -> &Ctx {
+        &self.ctx
+    }
+
+    pub fn ctx_mut(&mut self) -> &mut Ctx {
+        &mut self.ctx
+    }
+
+    pub fn replace_ctx(&mut self, ctx: Ctx) -> Ctx {
+        core::mem::replace(&mut self.ctx, ctx)
+    }
+
     pub fn default_ctx() -> Self
     where
         Ctx: Default,
+        Obs: Default,
     {
         Self::with_ctx(Default::default())
     }
@@ -137,49 +312,276 @@ impl<
         self.stacks.current_state()
     }
 
+    pub fn observer(&self) -> &Obs {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut Obs {
+        &mut self.observer
+    }
+
+    pub fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    pub fn actions(&self) -> usize {
+        self.actions
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint<NonTerminal, Token, Ctx>
+    where
+        NonTerminal: Clone,
+        Token: Clone,
+        Ctx: Clone,
+    {
+        Checkpoint {
+            stacks: self.stacks.clone(),
+            ctx: Some(self.ctx.clone()),
+            actions: self.actions,
+            marks: self.marks.clone(),
+            undo_log: self.undo_log.clone(),
+        }
+    }
+
+    pub fn checkpoint_stacks(&self) -> Checkpoint<NonTerminal, Token, Ctx>
+    where
+        NonTerminal: Clone,
+        Token: Clone,
+    {
+        Checkpoint {
+            stacks: self.stacks.clone(),
+            ctx: None,
+            actions: self.actions,
+            marks: self.marks.clone(),
+            undo_log: self.undo_log.clone(),
+        }
+    }
+
+    pub fn rollback(&mut self, checkpoint: Checkpoint<NonTerminal, Token, Ctx>) {
+        self.stacks = checkpoint.stacks;
+        if let Some(ctx) = checkpoint.ctx {
+            self.ctx = ctx;
+        }
+        self.actions = checkpoint.actions;
+        self.marks = checkpoint.marks;
+        self.undo_log = checkpoint.undo_log;
+    }
+
+    /// Remembers the height of the stacks, see `rollback_to_mark`. Instead of cloning the whole
+    /// stacks like `checkpoint` does, the reductions that follow clone the symbols they consume
+    /// from below the mark.
+    pub fn mark(&mut self) -> Mark
+    where
+        NonTerminal: Clone,
+        Token: Clone,
+    {
+        self.clone_entries = Some(clone_entries);
+        let generation = self.next_mark;
+        self.next_mark += 1;
+        self.marks.push(MarkEntry {
+            generation,
+            symbol_height: self.stacks.symbol_stack.len(),
+            actions: self.actions,
+            log_start: self.undo_log.len(),
+        });
+        Mark { generation }
+    }
+
+    /// Brings the stacks back to how they were at `mark`, releasing it and the marks taken after
+    /// it. The changes the semantic actions made to the context in the meantime are kept.
+    pub fn rollback_to_mark(&mut self, mark: Mark) -> Result<(), RollbackError> {
+        let index = self.mark_index(&mark).ok_or(RollbackError::MarkReleased)?;
+        let entry = self.marks[index];
+        self.marks.truncate(index);
+        let mut consumed = self.undo_log.split_off(entry.log_start);
+        consumed.retain(|undo| undo.position < entry.symbol_height);
+        // the first time a position got popped it still held what it held at the mark
+        consumed.sort_by_key(|undo| undo.position);
+        consumed.dedup_by_key(|undo| undo.position);
+        let lowest_height = consumed
+            .first()
+            .map_or(entry.symbol_height, |undo| undo.position);
+        self.stacks.symbol_stack.truncate(lowest_height);
+        self.stacks.state_stack.truncate(lowest_height + 1);
+        for undo in consumed {
+            self.stacks.state_stack.push(undo.state);
+            self.stacks.symbol_stack.push(undo.symbol);
+        }
+        self.actions = entry.actions;
+        self.release_undo_log();
+        Ok(())
+    }
+
+    /// Releases `mark` and the marks taken after it.
+    pub fn release_mark(&mut self, mark: Mark) {
+        if let Some(index) = self.mark_index(&mark) {
+            self.marks.truncate(index);
+            self.release_undo_log();
+        }
+    }
+
+    fn mark_index(&self, mark: &Mark) -> Option<usize> {
+        self.marks
+            .iter()
+            .position(|entry| entry.generation == mark.generation)
+    }
+
+    fn release_undo_log(&mut self) {
+        if self.marks.is_empty() {
+            self.undo_log.clear();
+        }
+    }
+
+    /// Clones the symbols that reducing by `prod` consumes from below a mark.
+    fn log_reduction(&mut self, prod: &Prod) {
+        let (Some(clone_entries), Some(marked_height)) = (
+            self.clone_entries,
+            self.marks.iter().map(|entry| entry.symbol_height).max(),
+        ) else {
+            return;
+        };
+        let height = self.stacks.symbol_stack.len();
+        let consumed = height - Tab::production_arity(prod)..height.min(marked_height);
+        if !consumed.is_empty() {
+            self.undo_log.extend(clone_entries(&self.stacks, consumed));
+        }
+    }
+
+    fn spend_action(&mut self) -> Result<(), LimitExceededReason> {
+        self.limits.check_actions(self.actions)?;
+        self.actions += 1;
+        Ok(())
+    }
+
+    /// Reduces by `prod` and goes to the state that follows its head, which is returned back if
+    /// there is none.
+    fn reduce_by(&mut self, prod: &Prod) -> Result<(), NonTerminal> {
+        self.observer.on_reduce(prod);
+        self.log_reduction(prod);
+        let head = prod.reduce(&mut self.ctx, &mut self.stacks);
+        let new_current_state = self.current_state();
+        let Some(next_state) = Tab::query_goto_table(new_current_state, &head) else {
+            self.observer.on_error(new_current_state);
+            return Err(head);
+        };
+        self.observer
+            .on_goto(&head, next_state, self.stacks.state_stack.len() + 1);
+        self.stacks.goto(next_state, head);
+        Ok(())
+    }
+
+    /// Checks the depth the stacks reach by popping `popped` symbols and pushing one, before
+    /// anything is pushed.
+    fn check_push(&mut self, popped: usize) -> Result<(), LimitExceededReason> {
+        let depth = self.stacks.symbol_stack.len() - popped + 1;
+        let checked = self.limits.check_stack_depth(depth);
+        if checked.is_err() {
+            self.observer.on_error(self.current_state());
+        }
+        checked
+    }
+
     fn parse_token(
         &mut self,
         token: Token,
     ) -> Result<ParseToken<Token>, ParseTokenErrorReason<NonTerminal, Token>> {
         let current_state = self.current_state();
+        if let Some(prod) = Tab::query_predicated_token_table(current_state, &token, |prod| {
+            prod.predicate(&self.ctx, Some(&token))
+        }) {
+            if let Err(reason) = self.check_push(Tab::production_arity(&prod)) {
+                return Err(ParseTokenErrorReason::LimitExceeded {
+                    reason,
+                    leftover_token: token,
+                });
+            }
+            self.reduce_by(&prod)
+                .map_err(|head| ParseTokenErrorReason::GotoNotFound {
+                    leftover_non_terminal: head,
+                })?;
+            return Ok(ParseToken::Reduced {
+                leftover_token: token,
+            });
+        }
         match Tab::query_token_table(current_state, &token) {
             Some(TokenAction::Shift(new_state)) => {
+                if let Err(reason) = self.check_push(0) {
+                    return Err(ParseTokenErrorReason::LimitExceeded {
+                        reason,
+                        leftover_token: token,
+                    });
+                }
+                self.observer
+                    .on_shift(&token, new_state, self.stacks.state_stack.len() + 1);
                 self.stacks.shift(new_state, token);
                 Ok(ParseToken::Shifted)
             }
             Some(TokenAction::Reduce(prod)) => {
-                let head = prod.reduce(&mut self.ctx, &mut self.stacks);
-                let new_current_state = self.current_state();
-                let Some(next_state) = Tab::query_goto_table(new_current_state, &head) else {
-                    return Err(ParseTokenErrorReason::GotoNotFound {
-                        leftover_non_terminal: head,
+                if let Err(reason) = self.check_push(Tab::production_arity(&prod)) {
+                    return Err(ParseTokenErrorReason::LimitExceeded {
+                        reason,
+                        leftover_token: token,
                     });
-                };
-                self.stacks.goto(next_state, head);
+                }
+                self.reduce_by(&prod)
+                    .map_err(|head| ParseTokenErrorReason::GotoNotFound {
+                        leftover_non_terminal: head,
+                    })?;
                 Ok(ParseToken::Reduced {
                     leftover_token: token,
                 })
             }
-            None => Err(ParseTokenErrorReason::ActionNotFound {
-                leftover_token: token,
-            }),
+            None => {
+                self.observer.on_error(current_state);
+                Err(ParseTokenErrorReason::ActionNotFound {
+                    leftover_token: token,
+                })
+            }
         }
     }
 
     pub fn consume_token(
         &mut self,
-        mut token: Token,
+        token: Token,
     ) -> Result<(), ParseTokenErrorReason<NonTerminal, Token>> {
+        self.consume_token_at(token, ())
+            .map_err(ParseOneError::into_token_reason)
+    }
+
+    /// Like `consume_token`, with the errors spanned by `span`.
+    pub fn consume_token_at<Span>(
+        &mut self,
+        mut token: Token,
+        span: Span,
+    ) -> Result<(), ParseOneError<NonTerminal, Token, Span>> {
         loop {
+            if let Err(reason) = self.spend_action() {
+                self.observer.on_error(self.current_state());
+                return Err(ParseOneError::LimitExceeded(LimitExceededError::new(
+                    reason,
+                    Some(token),
+                    span,
+                )));
+            }
+            // reductions can change the context, so the token is reclassified every time
+            token = Prod::reclassify(&mut self.ctx, token);
             match self.parse_token(token) {
-                Ok(ParseToken::Shifted) => {
-                    return Ok(());
-                }
-                Ok(ParseToken::Reduced { leftover_token }) => {
-                    token = leftover_token;
+                Ok(ParseToken::Shifted) => return Ok(()),
+                Ok(ParseToken::Reduced { leftover_token }) => token = leftover_token,
+                Err(ParseTokenErrorReason::LimitExceeded {
+                    reason,
+                    leftover_token,
+                }) => {
+                    return Err(ParseOneError::LimitExceeded(LimitExceededError::new(
+                        reason,
+                        Some(leftover_token),
+                        span,
+                    )));
                 }
                 Err(err) => {
-                    return Err(err);
+                    return Err(ParseOneError::ParseTokenError(ParseTokenError::new(
+                        err, span,
+                    )));
                 }
             }
         }
@@ -187,37 +589,79 @@ impl<
 
     fn parse_eof(&mut self) -> Result<ParseEof, ParseEofErrorReason<NonTerminal>> {
         let current_state = self.current_state();
+        if let Some(prod) =
+            Tab::query_predicated_eof_table(current_state, |prod| prod.predicate(&self.ctx, None))
+        {
+            self.check_push(Tab::production_arity(&prod))
+                .map_err(ParseEofErrorReason::LimitExceeded)?;
+            self.reduce_by(&prod)
+                .map_err(|head| ParseEofErrorReason::GotoNotFound {
+                    leftover_non_terminal: head,
+                })?;
+            return Ok(ParseEof::Reduced);
+        }
         match Tab::query_eof_table(current_state) {
             Some(EofAction::Reduce(prod)) => {
-                let head = prod.reduce(&mut self.ctx, &mut self.stacks);
-                let new_current_state = self.current_state();
-                let Some(next_state) = Tab::query_goto_table(new_current_state, &head) else {
-                    return Err(ParseEofErrorReason::GotoNotFound {
+                self.check_push(Tab::production_arity(&prod))
+                    .map_err(ParseEofErrorReason::LimitExceeded)?;
+                self.reduce_by(&prod)
+                    .map_err(|head| ParseEofErrorReason::GotoNotFound {
                         leftover_non_terminal: head,
-                    });
-                };
-                self.stacks.goto(next_state, head);
+                    })?;
                 Ok(ParseEof::Reduced)
             }
-            Some(EofAction::Accept) => Ok(ParseEof::Accepted),
-            None => Err(ParseEofErrorReason::ActionNotFound),
+            Some(EofAction::Accept) => {
+                self.observer.on_accept();
+                Ok(ParseEof::Accepted)
+            }
+            None => {
+                self.observer.on_error(current_state);
+                Err(ParseEofErrorReason::ActionNotFound)
+            }
         }
     }
 
-    pub fn consume_eof(&mut self) -> Result<StartSymbol, ParseEofError<NonTerminal>>
-    where
-        StartSymbol: From<NonTerminal>,
-    {
+    pub fn consume_eof(&mut self) -> Result<StartSymbol, ParseEofError<NonTerminal>> {
+        self.consume_eof_at(())
+            .map_err(|err| err.into_eof_error(self.current_state()))
+    }
+
+    /// Like `consume_eof`, with the errors spanned by `span`.
+    pub fn consume_eof_at<Span>(
+        &mut self,
+        span: Span,
+    ) -> Result<StartSymbol, ParseOneError<NonTerminal, Token, Span>> {
+        // the reductions are only done once the end of input is known to be accepted, so a
+        // parser that fails here keeps the stacks it can be resumed from
+        if let Some(Err(state)) = self.follow_eof(&mut self.stacks.state_stack.clone()) {
+            self.observer.on_error(state);
+            return Err(ParseOneError::ParseEofError(ParseEofError::new(
+                ParseEofErrorReason::ActionNotFound,
+                state,
+            )));
+        }
         loop {
+            if let Err(reason) = self.spend_action() {
+                self.observer.on_error(self.current_state());
+                return Err(ParseOneError::LimitExceeded(LimitExceededError::new(
+                    reason, None, span,
+                )));
+            }
             match self.parse_eof() {
                 Ok(ParseEof::Accepted) => {
                     break;
                 }
-                Ok(ParseEof::Reduced) => {
-                    continue;
+                Ok(ParseEof::Reduced) => {}
+                Err(ParseEofErrorReason::LimitExceeded(reason)) => {
+                    return Err(ParseOneError::LimitExceeded(LimitExceededError::new(
+                        reason, None, span,
+                    )));
                 }
                 Err(err) => {
-                    return Err(ParseEofError::new(err));
+                    return Err(ParseOneError::ParseEofError(ParseEofError::new(
+                        err,
+                        self.current_state(),
+                    )));
                 }
             }
         }
@@ -226,33 +670,47 @@ impl<
             unreachable!()
         };
 
-        Ok(non_terminal.into())
+        Ok(Tab::accept(non_terminal))
+    }
+
+    fn consume_tokens(
+        &mut self,
+        tokens: impl IntoIterator<Item = Token>,
+    ) -> Result<StartSymbol, ParseOneError<NonTerminal, Token, usize>> {
+        for token in tokens {
+            self.consume_token_at(token, self.offset)?;
+            self.offset += 1;
+        }
+
+        self.consume_eof_at(self.offset)
     }
 
     pub fn do_parse(
         mut self,
         tokens: impl IntoIterator<Item = Token>,
     ) -> ParseResult<Self, NonTerminal, Token, (StartSymbol, Ctx)> {
-        for (span, token) in tokens.into_iter().enumerate() {
-            if let Err(err) = self
-                .consume_token(token)
-                .map_err(|err| ParseTokenError::new(err, span))
-                .map_err(ParseOneError::ParseTokenError)
-            {
-                return Err(ParseError::new(self, err, ()));
-            }
-        }
-
-        match self.consume_eof().map_err(ParseOneError::ParseEofError) {
+        match self.consume_tokens(tokens) {
             Err(err) => Err(ParseError::new(self, err, ())),
             Ok(res) => Ok((res, self.ctx)),
         }
     }
 
+    pub fn do_parse_mut(
+        &mut self,
+        tokens: impl IntoIterator<Item = Token>,
+    ) -> ParseResult<&mut Self, NonTerminal, Token, StartSymbol> {
+        self.reset();
+        self.consume_tokens(tokens)
+            .map_err(|err| ParseError::new(self, err, ()))
+    }
+
     pub fn parse_with_ctx(
         ctx: Ctx,
         tokens: impl IntoIterator<Item = Token>,
-    ) -> ParseResult<Self, NonTerminal, Token, (StartSymbol, Ctx)> {
+    ) -> ParseResult<Self, NonTerminal, Token, (StartSymbol, Ctx)>
+    where
+        Obs: Default,
+    {
         Self::with_ctx(ctx).do_parse(tokens)
     }
 
@@ -261,70 +719,403 @@ impl<
     ) -> ParseResult<Self, NonTerminal, Token, (StartSymbol, Ctx)>
     where
         Ctx: Default,
+        Obs: Default,
     {
         Self::default_ctx().do_parse(tokens)
     }
 
-    pub fn do_lex_parse<'source>(
-        mut self,
+    fn relex<'source>(
+        &self,
+        states: &[usize],
+        token: Token,
         source: &'source Token::Source,
-    ) -> LexParseResult<'source, Self, NonTerminal, Token, (StartSymbol, Ctx)>
+        span: &Range<usize>,
+    ) -> Token
+    where
+        Token: Logos<'source>,
+    {
+        let Some(&state) = states.last() else {
+            return token;
+        };
+        if !self.contextual_lexing || Self::has_action(state, &token) {
+            return token;
+        }
+        Tab::reinterpret_token(&token, source, span.clone(), |candidate| {
+            Self::has_action(state, candidate)
+        })
+        .unwrap_or(token)
+    }
+
+    fn has_action(state: usize, token: &Token) -> bool {
+        Tab::query_token_table(state, token).is_some()
+            || Tab::query_predicated_token_table(state, token, |_| true).is_some()
+    }
+
+    fn consume_source<'source>(
+        &mut self,
+        source: &'source Token::Source,
+    ) -> ConsumeSourceResult<NonTerminal, Token, Token::Error, StartSymbol>
     where
         Token: Logos<'source>,
         Token::Extras: Default,
     {
+        let base = self.offset;
         for (token, span) in Token::lexer(source).spanned() {
-            let mut token = match token {
-                Ok(token) => token,
+            self.offset = base + span.start;
+            let token = match token {
+                Ok(token) => self.relex(&self.stacks.state_stack, token, source, &span),
                 Err(err) => {
-                    return Err(LexParseError::LexError(LexError::new(
-                        self, err, span, source,
-                    )));
+                    return Err(LexParseError::LexError((err, self.offset..base + span.end)));
                 }
             };
 
-            loop {
-                match self.parse_token(token) {
-                    Ok(ParseToken::Shifted) => {
-                        break;
-                    }
-                    Ok(ParseToken::Reduced { leftover_token }) => {
-                        token = leftover_token;
-                    }
-                    Err(err) => {
-                        return Err(LexParseError::ParseError(ParseError::new(
-                            self,
-                            ParseOneError::ParseTokenError(ParseTokenError::new(err, span)),
-                            source,
-                        )));
+            self.consume_token_at(token, self.offset..base + span.end)
+                .map_err(LexParseError::ParseError)?;
+        }
+
+        self.offset = base + source.len();
+        self.consume_eof_at(self.offset..self.offset)
+            .map_err(LexParseError::ParseError)
+    }
+
+    fn consume_source_prefix<'source>(
+        &mut self,
+        source: &'source Token::Source,
+    ) -> ConsumeSourceResult<NonTerminal, Token, Token::Error, (StartSymbol, Range<usize>)>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        for (token, span) in Token::lexer(source).spanned() {
+            let token = token
+                .map(|token| self.relex(&self.stacks.state_stack, token, source, &span))
+                .map(|token| Prod::reclassify(&mut self.ctx, token));
+            let stop = match &token {
+                Ok(token) => self.can_accept_before(Some(token)),
+                Err(_) => self.can_accept_before(None),
+            };
+            if stop {
+                let rest_span = span.start..source.len();
+                return self
+                    .consume_eof_at(span.start..span.start)
+                    .map(|res| (res, rest_span))
+                    .map_err(LexParseError::ParseError);
+            }
+            let token = match token {
+                Ok(token) => token,
+                Err(err) => return Err(LexParseError::LexError((err, span))),
+            };
+
+            self.consume_token_at(token, span)
+                .map_err(LexParseError::ParseError)?;
+        }
+
+        self.consume_eof_at(source.len()..source.len())
+            .map(|res| (res, source.len()..source.len()))
+            .map_err(LexParseError::ParseError)
+    }
+
+    /// Whether the input read so far can be accepted and `token` (if any) could not continue it.
+    fn can_accept_before(&self, token: Option<&Token>) -> bool {
+        if let Some(token) = token {
+            if let Some(TokenAction::Shift(_)) = Tab::query_token_table(self.current_state(), token)
+            {
+                return false;
+            }
+            if self
+                .simulate_token(&mut self.stacks.state_stack.clone(), token)
+                .is_some()
+            {
+                return false;
+            }
+        }
+        self.simulate_eof(&mut self.stacks.state_stack.clone(), ())
+            .is_complete()
+    }
+
+    fn consume_source_recovering<'source>(
+        &mut self,
+        source: &'source Token::Source,
+        recovery: LexRecovery,
+        lex_errors: &mut Vec<LexError<'source, (), Token>>,
+    ) -> Result<StartSymbol, ParseOneError<NonTerminal, Token, Range<usize>>>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        for (token, span) in Token::lexer(source).spanned() {
+            let token = match token {
+                Ok(token) => self.relex(&self.stacks.state_stack, token, source, &span),
+                Err(err) => {
+                    match lex_errors.last_mut() {
+                        Some(last)
+                            if recovery == LexRecovery::Resynchronize
+                                && last.span.end == span.start =>
+                        {
+                            last.span.end = span.end
+                        }
+                        _ => lex_errors.push(LexError::new((), err, span, source)),
                     }
+                    continue;
                 }
-            }
+            };
+
+            self.consume_token_at(token, span)?;
         }
 
+        self.consume_eof_at(source.len()..source.len())
+    }
+
+    fn simulate_reduction(states: &mut Vec<usize>, production: &Prod) -> Option<()> {
+        states.truncate(
+            states
+                .len()
+                .checked_sub(Tab::production_arity(production))?,
+        );
+        let next_state =
+            Tab::query_goto_table_by_id(*states.last()?, Tab::production_head(production))?;
+        states.push(next_state);
+        Some(())
+    }
+
+    fn simulate_token(&self, states: &mut Vec<usize>, token: &Token) -> Option<()> {
         loop {
-            match self.parse_eof() {
-                Ok(ParseEof::Accepted) => {
-                    break;
+            let current_state = *states.last()?;
+            if let Some(production) =
+                Tab::query_predicated_token_table(current_state, token, |production| {
+                    production.predicate(&self.ctx, Some(token))
+                })
+            {
+                Self::simulate_reduction(states, &production)?;
+                continue;
+            }
+            match Tab::query_token_table(current_state, token)? {
+                TokenAction::Shift(new_state) => {
+                    states.push(new_state);
+                    return Some(());
                 }
-                Ok(ParseEof::Reduced) => {
-                    continue;
+                TokenAction::Reduce(production) => Self::simulate_reduction(states, &production)?,
+            }
+        }
+    }
+
+    /// Follows the end of input from `states`, failing with the state that has no action for it,
+    /// or `None` if a reduction can't be followed.
+    fn follow_eof(&self, states: &mut Vec<usize>) -> Option<Result<(), usize>> {
+        loop {
+            let current_state = *states.last()?;
+            if let Some(production) = Tab::query_predicated_eof_table(current_state, |production| {
+                production.predicate(&self.ctx, None)
+            }) {
+                Self::simulate_reduction(states, &production)?;
+                continue;
+            }
+            match Tab::query_eof_table(current_state) {
+                Some(EofAction::Reduce(production)) => {
+                    Self::simulate_reduction(states, &production)?
                 }
-                Err(err) => {
-                    return Err(LexParseError::ParseError(ParseError::new(
-                        self,
-                        ParseOneError::ParseEofError(ParseEofError::new(err)),
-                        source,
-                    )));
+                Some(EofAction::Accept) => return Some(Ok(())),
+                None => return Some(Err(current_state)),
+            }
+        }
+    }
+
+    fn simulate_eof<Error>(
+        &self,
+        states: &mut Vec<usize>,
+        error: Error,
+    ) -> InputStatus<(), Tab::TokenSet, Error> {
+        match self.follow_eof(states) {
+            Some(Ok(())) => InputStatus::Complete(()),
+            Some(Err(state)) => InputStatus::Incomplete {
+                expected: Tab::token_set_in_state(state),
+            },
+            None => InputStatus::Invalid(error),
+        }
+    }
+
+    /// Steps `tokens` through the tables without running semantic actions or touching the
+    /// stacks. The tokens go through the `reclassify` hook, which gets the context.
+    pub fn check(
+        &mut self,
+        tokens: impl IntoIterator<Item = Token>,
+    ) -> InputStatus<(), Tab::TokenSet, usize> {
+        let mut states = self.stacks.state_stack.clone();
+        let mut tokens_count = 0;
+        for (index, token) in tokens.into_iter().enumerate() {
+            let token = Prod::reclassify(&mut self.ctx, token);
+            if self.simulate_token(&mut states, &token).is_none() {
+                return InputStatus::Invalid(index);
+            }
+            tokens_count += 1;
+        }
+        self.simulate_eof(&mut states, tokens_count)
+    }
+
+    /// Like `check`, on the tokens of `source`.
+    pub fn check_source<'source>(
+        &mut self,
+        source: &'source Token::Source,
+    ) -> InputStatus<(), Tab::TokenSet, Range<usize>>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        let mut states = self.stacks.state_stack.clone();
+        for (token, span) in Token::lexer(source).spanned() {
+            let token = token
+                .map(|token| self.relex(&states, token, source, &span))
+                .map(|token| Prod::reclassify(&mut self.ctx, token));
+            match token {
+                Ok(token) if self.simulate_token(&mut states, &token).is_some() => {}
+                _ => return InputStatus::Invalid(span),
+            }
+        }
+        self.simulate_eof(&mut states, source.len()..source.len())
+    }
+
+    /// The tokens and non-terminals expected at `offset`, found like `check` does. A lexer error before `offset` stops
+    /// the scan: the completions are those of the state reached before it.
+    pub fn completions_at<'source>(
+        &mut self,
+        source: &'source Token::Source,
+        offset: usize,
+    ) -> Option<Completions<Tab::TokenSet>>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        let mut states = self.stacks.state_stack.clone();
+        for (token, span) in Token::lexer(source).spanned() {
+            if span.end > offset {
+                break;
+            }
+            let Ok(token) = token else {
+                break;
+            };
+            let token = self.relex(&states, token, source, &span);
+            let token = Prod::reclassify(&mut self.ctx, token);
+            self.simulate_token(&mut states, &token)?;
+        }
+        let state = *states.last()?;
+        Some(Completions {
+            state,
+            tokens: Tab::token_set_in_state(state),
+            token_names: Tab::tokens_in_state(state),
+            non_terminals: Tab::non_terminals_in_state(state),
+        })
+    }
+
+    pub fn do_lex_parse<'source>(
+        mut self,
+        source: &'source Token::Source,
+    ) -> LexParseResult<'source, Self, NonTerminal, Token, (StartSymbol, Ctx)>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        let source_offset = self.offset;
+        match self.consume_source(source) {
+            Err(LexParseError::LexError((err, span))) => Err(LexParseError::LexError(
+                LexError::new(self, err, span, source).with_source_offset(source_offset),
+            )),
+            Err(LexParseError::ParseError(err)) => Err(LexParseError::ParseError(
+                ParseError::new(self, err, source).with_source_offset(source_offset),
+            )),
+            Ok(res) => Ok((res, self.ctx)),
+        }
+    }
+
+    pub fn do_lex_parse_mut<'source>(
+        &mut self,
+        source: &'source Token::Source,
+    ) -> LexParseResult<'source, &mut Self, NonTerminal, Token, StartSymbol>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        self.reset();
+        match self.consume_source(source) {
+            Err(LexParseError::LexError((err, span))) => Err(LexParseError::LexError(
+                LexError::new(self, err, span, source),
+            )),
+            Err(LexParseError::ParseError(err)) => Err(LexParseError::ParseError(ParseError::new(
+                self, err, source,
+            ))),
+            Ok(res) => Ok(res),
+        }
+    }
+
+    pub fn do_lex_parse_prefix<'source>(
+        mut self,
+        source: &'source Token::Source,
+    ) -> LexParseResult<'source, Self, NonTerminal, Token, (StartSymbol, Ctx, Range<usize>)>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        match self.consume_source_prefix(source) {
+            Err(LexParseError::LexError((err, span))) => Err(LexParseError::LexError(
+                LexError::new(self, err, span, source),
+            )),
+            Err(LexParseError::ParseError(err)) => Err(LexParseError::ParseError(ParseError::new(
+                self, err, source,
+            ))),
+            Ok((res, rest_span)) => Ok((res, self.ctx, rest_span)),
+        }
+    }
+
+    pub fn do_lex_parse_interactive<'source>(
+        self,
+        source: &'source Token::Source,
+    ) -> LexParseStatus<'source, Self, NonTerminal, Token, Tab::TokenSet, (StartSymbol, Ctx)>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        match self.do_lex_parse(source) {
+            Ok(res) => InputStatus::Complete(res),
+            Err(LexParseError::ParseError(err)) if err.parse_one_error.is_unexpected_eof() => {
+                InputStatus::Incomplete {
+                    expected: err.expected(),
                 }
             }
+            Err(err) => InputStatus::Invalid(err),
         }
+    }
 
-        let Symbol::NonTerminal(non_terminal) = self.stacks.symbol_stack.pop().unwrap() else {
-            unreachable!()
+    pub fn do_lex_parse_recovering<'source>(
+        mut self,
+        source: &'source Token::Source,
+        recovery: LexRecovery,
+    ) -> RecoveringLexParseResult<'source, Self, NonTerminal, Token, (StartSymbol, Ctx)>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        let mut lex_errors = Vec::new();
+        let result = match self.consume_source_recovering(source, recovery, &mut lex_errors) {
+            Err(err) => Err(ParseError::new(self, err, source)),
+            Ok(res) => Ok((res, self.ctx)),
         };
+        Recovered::new(result, lex_errors)
+    }
 
-        Ok((non_terminal.into(), self.ctx))
+    pub fn do_lex_parse_recovering_mut<'source>(
+        &mut self,
+        source: &'source Token::Source,
+        recovery: LexRecovery,
+    ) -> RecoveringLexParseResult<'source, &mut Self, NonTerminal, Token, StartSymbol>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        self.reset();
+        let mut lex_errors = Vec::new();
+        let result = self
+            .consume_source_recovering(source, recovery, &mut lex_errors)
+            .map_err(|err| ParseError::new(self, err, source));
+        Recovered::new(result, lex_errors)
     }
 
     pub fn lex_parse_with_ctx<'source>(
@@ -334,6 +1125,7 @@ impl<
     where
         Token: Logos<'source>,
         Token::Extras: Default,
+        Obs: Default,
     {
         Self::with_ctx(ctx).do_lex_parse(source)
     }
@@ -345,6 +1137,7 @@ impl<
         Token: Logos<'source>,
         Token::Extras: Default,
         Ctx: Default,
+        Obs: Default,
     {
         Self::default_ctx().do_lex_parse(source)
     }
@@ -353,10 +1146,59 @@ impl<
 impl<
     NonTerminal,
     Token,
-    StartSymbol: From<NonTerminal>,
+    StartSymbol,
+    Prod: Reduce<NonTerminal, Token, Ctx>,
+    Tab: Tables<NonTerminal, Token, Prod> + EntryPoint<NonTerminal, StartSymbol>,
+    Ctx,
+    Obs: ParseObserver<NonTerminal, Token, Prod>,
+    Span,
+    Source,
+>
+    ParseError<
+        Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>,
+        NonTerminal,
+        Token,
+        Span,
+        Source,
+    >
+{
+    /// Continues with `tokens` in place of the rejected token, `resume_with` gives it back.
+    pub fn resume(
+        self,
+        tokens: impl IntoIterator<Item = Token>,
+    ) -> ResumeResult<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs> {
+        self.parser.do_parse(tokens)
+    }
+
+    pub fn resume_with<Tokens: IntoIterator<Item = Token>>(
+        self,
+        fix: impl FnOnce(Option<Token>) -> Tokens,
+    ) -> ResumeResult<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs> {
+        let (parser, leftover_token) = self.into_parts();
+        parser.do_parse(fix(leftover_token))
+    }
+
+    /// Continues with the tokens of `source_rest` in place of the rejected token.
+    pub fn resume_lex<'source>(
+        self,
+        source_rest: &'source Token::Source,
+    ) -> ResumeLexResult<'source, NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        self.parser.do_lex_parse(source_rest)
+    }
+}
+
+impl<
+    NonTerminal,
+    Token,
+    StartSymbol,
     Prod: Reduce<NonTerminal, Token, ()>,
-    Tab: Tables<NonTerminal, Token, Prod>,
-> Parser<NonTerminal, Token, StartSymbol, Prod, Tab, ()>
+    Tab: Tables<NonTerminal, Token, Prod> + EntryPoint<NonTerminal, StartSymbol>,
+    Obs: ParseObserver<NonTerminal, Token, Prod> + Default,
+> Parser<NonTerminal, Token, StartSymbol, Prod, Tab, (), Obs>
 {
     pub fn new() -> Self {
         Self::with_ctx(())
@@ -377,19 +1219,96 @@ impl<
     {
         Self::new().do_lex_parse(source).map(|ok| ok.0)
     }
+
+    pub fn lex_parse_prefix<'source>(
+        source: &'source Token::Source,
+    ) -> LexParseResult<'source, Self, NonTerminal, Token, (StartSymbol, Range<usize>)>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        Self::new()
+            .do_lex_parse_prefix(source)
+            .map(|(res, (), rest_span)| (res, rest_span))
+    }
+
+    pub fn lex_parse_interactive<'source>(
+        source: &'source Token::Source,
+    ) -> LexParseStatus<'source, Self, NonTerminal, Token, Tab::TokenSet, StartSymbol>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        match Self::new().do_lex_parse_interactive(source) {
+            InputStatus::Complete((res, ())) => InputStatus::Complete(res),
+            InputStatus::Incomplete { expected } => InputStatus::Incomplete { expected },
+            InputStatus::Invalid(err) => InputStatus::Invalid(err),
+        }
+    }
+
+    pub fn lex_parse_recovering<'source>(
+        source: &'source Token::Source,
+        recovery: LexRecovery,
+    ) -> RecoveringLexParseResult<'source, Self, NonTerminal, Token, StartSymbol>
+    where
+        Token: Logos<'source>,
+        Token::Extras: Default,
+    {
+        let Recovered { result, lex_errors } =
+            Self::new().do_lex_parse_recovering(source, recovery);
+        Recovered::new(result.map(|ok| ok.0), lex_errors)
+    }
 }
 impl<
     NonTerminal,
     Token,
-    StartSymbol: From<NonTerminal>,
+    StartSymbol,
     Prod: Reduce<NonTerminal, Token, Ctx>,
-    Tab: Tables<NonTerminal, Token, Prod>,
+    Tab: Tables<NonTerminal, Token, Prod> + EntryPoint<NonTerminal, StartSymbol>,
     Ctx,
-> Default for Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx>
+    Obs: ParseObserver<NonTerminal, Token, Prod>,
+> Default for Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>
 where
     Ctx: Default,
+    Obs: Default,
 {
     fn default() -> Self {
         Self::default_ctx()
     }
 }
+
+impl<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs> ParserState
+    for Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx, Obs>
+where
+    Tab: Tables<NonTerminal, Token, Prod>,
+{
+    type TokenSet = Tab::TokenSet;
+
+    fn current_state(&self) -> usize {
+        self.stacks.current_state()
+    }
+
+    fn tokens_in_state(&self, state: usize) -> &'static [&'static str] {
+        Tab::tokens_in_state(state)
+    }
+
+    fn token_set_in_state(&self, state: usize) -> Self::TokenSet {
+        Tab::token_set_in_state(state)
+    }
+}
+
+impl<Parser: ParserState> ParserState for &mut Parser {
+    type TokenSet = Parser::TokenSet;
+
+    fn current_state(&self) -> usize {
+        (**self).current_state()
+    }
+
+    fn tokens_in_state(&self, state: usize) -> &'static [&'static str] {
+        (**self).tokens_in_state(state)
+    }
+
+    fn token_set_in_state(&self, state: usize) -> Self::TokenSet {
+        (**self).token_set_in_state(state)
+    }
+}
@@ -0,0 +1,56 @@
+use core::fmt::Display;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_stack_depth: Option<usize>,
+    pub max_actions: Option<usize>,
+}
+
+impl ParseLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_stack_depth(mut self, max_stack_depth: usize) -> Self {
+        self.max_stack_depth = Some(max_stack_depth);
+        self
+    }
+
+    pub fn max_actions(mut self, max_actions: usize) -> Self {
+        self.max_actions = Some(max_actions);
+        self
+    }
+
+    pub(crate) fn check_stack_depth(&self, depth: usize) -> Result<(), LimitExceededReason> {
+        match self.max_stack_depth {
+            Some(max) if depth > max => Err(LimitExceededReason::StackDepth(max)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_actions(&self, actions: usize) -> Result<(), LimitExceededReason> {
+        match self.max_actions {
+            Some(max) if actions >= max => Err(LimitExceededReason::Actions(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceededReason {
+    StackDepth(usize),
+    Actions(usize),
+}
+
+impl Display for LimitExceededReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LimitExceededReason::StackDepth(max) => {
+                write!(f, "maximum stack depth of {max} exceeded")
+            }
+            LimitExceededReason::Actions(max) => {
+                write!(f, "maximum number of actions ({max}) exceeded")
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+pub trait ParseObserver<NonTerminal, Token, Prod> {
+    fn on_shift(&mut self, _token: &Token, _new_state: usize, _depth: usize) {}
+    fn on_reduce(&mut self, _production: &Prod) {}
+    fn on_goto(&mut self, _non_terminal: &NonTerminal, _new_state: usize, _depth: usize) {}
+    fn on_error(&mut self, _state: usize) {}
+    fn on_accept(&mut self) {}
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoObserver;
+
+impl<NonTerminal, Token, Prod> ParseObserver<NonTerminal, Token, Prod> for NoObserver {}
+
+impl<NonTerminal, Token, Prod, Obs> ParseObserver<NonTerminal, Token, Prod> for &mut Obs
+where
+    Obs: ParseObserver<NonTerminal, Token, Prod>,
+{
+    fn on_shift(&mut self, token: &Token, new_state: usize, depth: usize) {
+        (**self).on_shift(token, new_state, depth);
+    }
+
+    fn on_reduce(&mut self, production: &Prod) {
+        (**self).on_reduce(production);
+    }
+
+    fn on_goto(&mut self, non_terminal: &NonTerminal, new_state: usize, depth: usize) {
+        (**self).on_goto(non_terminal, new_state, depth);
+    }
+
+    fn on_error(&mut self, state: usize) {
+        (**self).on_error(state);
+    }
+
+    fn on_accept(&mut self) {
+        (**self).on_accept();
+    }
+}
@@ -0,0 +1,8 @@
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LexRecovery {
+    /// Every invalid span reported by the lexer becomes its own error.
+    #[default]
+    SkipInvalid,
+    /// Adjacent invalid spans are merged, so a run of bad input up to the next valid token is a single error.
+    Resynchronize,
+}
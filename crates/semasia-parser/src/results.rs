@@ -1,14 +1,14 @@
-use std::{
+use alloc::{format, vec::Vec};
+use core::{
     error::Error,
     fmt::{Debug, Display},
     ops::Range,
 };
 
-use colored::Colorize;
 use itertools::Itertools;
 use logos::Logos;
 
-use crate::{Parser, Tables};
+use crate::{Diagnostic, Kinded, LimitExceededReason, ParserState, ToDiagnostic};
 
 #[derive(Debug)]
 pub enum ParseToken<Token> {
@@ -24,15 +24,24 @@ pub enum ParseEof {
 
 #[derive(Debug)]
 pub enum ParseTokenErrorReason<NonTerminal, Token> {
-    ActionNotFound { leftover_token: Token },
-    GotoNotFound { leftover_non_terminal: NonTerminal },
+    ActionNotFound {
+        leftover_token: Token,
+    },
+    GotoNotFound {
+        leftover_non_terminal: NonTerminal,
+    },
+    LimitExceeded {
+        reason: LimitExceededReason,
+        leftover_token: Token,
+    },
 }
 
 impl<NonTerminal, Token> Display for ParseTokenErrorReason<NonTerminal, Token> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             ParseTokenErrorReason::ActionNotFound { .. } => write!(f, "action not found"),
             ParseTokenErrorReason::GotoNotFound { .. } => write!(f, "goto action not found"),
+            ParseTokenErrorReason::LimitExceeded { reason, .. } => write!(f, "{reason}"),
         }
     }
 }
@@ -49,10 +58,14 @@ impl<NonTerminal, Token, Span> ParseTokenError<NonTerminal, Token, Span> {
     pub fn new(reason: ParseTokenErrorReason<NonTerminal, Token>, span: Span) -> Self {
         Self { reason, span }
     }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl<NonTerminal, Token, Span> Display for ParseTokenError<NonTerminal, Token, Span> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.reason)
     }
 }
@@ -63,13 +76,15 @@ impl<NonTerminal, Token, Span> Error for ParseTokenError<NonTerminal, Token, Spa
 pub enum ParseEofErrorReason<NonTerminal> {
     ActionNotFound,
     GotoNotFound { leftover_non_terminal: NonTerminal },
+    LimitExceeded(LimitExceededReason),
 }
 
 impl<NonTerminal> Display for ParseEofErrorReason<NonTerminal> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             ParseEofErrorReason::ActionNotFound => write!(f, "action not found"),
             ParseEofErrorReason::GotoNotFound { .. } => write!(f, "goto not found"),
+            ParseEofErrorReason::LimitExceeded(reason) => write!(f, "{reason}"),
         }
     }
 }
@@ -79,26 +94,148 @@ impl<NonTerminal> Error for ParseEofErrorReason<NonTerminal> where Self: Debug {
 #[derive(Debug)]
 pub struct ParseEofError<NonTerminal> {
     reason: ParseEofErrorReason<NonTerminal>,
+    state: usize,
 }
 
 impl<NonTerminal> ParseEofError<NonTerminal> {
-    pub fn new(reason: ParseEofErrorReason<NonTerminal>) -> Self {
-        Self { reason }
+    pub fn new(reason: ParseEofErrorReason<NonTerminal>, state: usize) -> Self {
+        Self { reason, state }
+    }
+
+    /// The state the end of input was met in, which can be ahead of the parser when the
+    /// reductions leading to it weren't done.
+    pub fn state(&self) -> usize {
+        self.state
     }
 }
 
 impl<NonTerminal> Display for ParseEofError<NonTerminal> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.reason)
     }
 }
 
 impl<NonTerminal> Error for ParseEofError<NonTerminal> where Self: Debug {}
 
+#[derive(Debug)]
+pub struct LimitExceededError<Token, Span> {
+    reason: LimitExceededReason,
+    leftover_token: Option<Token>,
+    span: Span,
+}
+
+impl<Token, Span> LimitExceededError<Token, Span> {
+    pub fn new(reason: LimitExceededReason, leftover_token: Option<Token>, span: Span) -> Self {
+        Self {
+            reason,
+            leftover_token,
+            span,
+        }
+    }
+
+    pub fn reason(&self) -> LimitExceededReason {
+        self.reason
+    }
+
+    pub fn leftover_token(&self) -> Option<&Token> {
+        self.leftover_token.as_ref()
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl<Token, Span> Display for LimitExceededError<Token, Span> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl<Token, Span> Error for LimitExceededError<Token, Span> where Self: Debug {}
+
 #[derive(Debug)]
 pub enum ParseOneError<NonTerminal, Token, Span> {
     ParseTokenError(ParseTokenError<NonTerminal, Token, Span>),
     ParseEofError(ParseEofError<NonTerminal>),
+    LimitExceeded(LimitExceededError<Token, Span>),
+}
+
+impl<NonTerminal, Token, Span> ParseOneError<NonTerminal, Token, Span> {
+    pub fn leftover_token(&self) -> Option<&Token> {
+        match self {
+            ParseOneError::ParseTokenError(ParseTokenError {
+                reason: ParseTokenErrorReason::ActionNotFound { leftover_token },
+                ..
+            }) => Some(leftover_token),
+            ParseOneError::LimitExceeded(limit_exceeded_error) => {
+                limit_exceeded_error.leftover_token.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Where the error happened, `None` when the tables rejected the end of input.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            ParseOneError::ParseTokenError(parse_token_error) => Some(&parse_token_error.span),
+            ParseOneError::ParseEofError(_) => None,
+            ParseOneError::LimitExceeded(limit_exceeded_error) => Some(&limit_exceeded_error.span),
+        }
+    }
+
+    pub fn is_unexpected_eof(&self) -> bool {
+        matches!(
+            self,
+            ParseOneError::ParseEofError(ParseEofError {
+                reason: ParseEofErrorReason::ActionNotFound,
+                ..
+            })
+        )
+    }
+
+    pub fn into_leftover_token(self) -> Option<Token> {
+        match self {
+            ParseOneError::ParseTokenError(ParseTokenError {
+                reason: ParseTokenErrorReason::ActionNotFound { leftover_token },
+                ..
+            }) => Some(leftover_token),
+            ParseOneError::LimitExceeded(limit_exceeded_error) => {
+                limit_exceeded_error.leftover_token
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<NonTerminal, Token> ParseOneError<NonTerminal, Token, ()> {
+    /// The error of `consume_token`, which never meets the end of input.
+    pub(crate) fn into_token_reason(self) -> ParseTokenErrorReason<NonTerminal, Token> {
+        match self {
+            ParseOneError::ParseTokenError(parse_token_error) => parse_token_error.reason,
+            ParseOneError::LimitExceeded(LimitExceededError {
+                reason,
+                leftover_token: Some(leftover_token),
+                ..
+            }) => ParseTokenErrorReason::LimitExceeded {
+                reason,
+                leftover_token,
+            },
+            _ => unreachable!("consuming a token ended the input"),
+        }
+    }
+
+    /// The error of `consume_eof`, which never has a token left over.
+    pub(crate) fn into_eof_error(self, state: usize) -> ParseEofError<NonTerminal> {
+        match self {
+            ParseOneError::ParseEofError(parse_eof_error) => parse_eof_error,
+            ParseOneError::LimitExceeded(limit_exceeded_error) => ParseEofError::new(
+                ParseEofErrorReason::LimitExceeded(limit_exceeded_error.reason),
+                state,
+            ),
+            ParseOneError::ParseTokenError(_) => unreachable!("the end of input had a token"),
+        }
+    }
 }
 
 impl<NonTerminal, Token, Span> Error for ParseOneError<NonTerminal, Token, Span> where
@@ -111,6 +248,8 @@ pub struct ParseError<Parser, NonTerminal, Token, Span, Source> {
     pub parser: Parser,
     pub parse_one_error: ParseOneError<NonTerminal, Token, Span>,
     pub source: Source,
+    /// Where `source` starts in the whole input, which the spans are relative to: it isn't 0 after a resume.
+    pub source_offset: usize,
 }
 
 impl<Parser, NonTerminal, Token, Span, Source>
@@ -125,112 +264,139 @@ impl<Parser, NonTerminal, Token, Span, Source>
             parser,
             parse_one_error,
             source,
+            source_offset: 0,
+        }
+    }
+
+    pub fn with_source_offset(mut self, source_offset: usize) -> Self {
+        self.source_offset = source_offset;
+        self
+    }
+
+    pub fn leftover_token(&self) -> Option<&Token> {
+        self.parse_one_error.leftover_token()
+    }
+
+    pub fn into_parts(self) -> (Parser, Option<Token>) {
+        (self.parser, self.parse_one_error.into_leftover_token())
+    }
+}
+
+impl<Parser, NonTerminal, Token, Span, Source> ParseError<Parser, NonTerminal, Token, Span, Source>
+where
+    Parser: ParserState,
+{
+    /// The state whose tokens were expected.
+    fn expected_state(&self) -> usize {
+        match &self.parse_one_error {
+            ParseOneError::ParseEofError(parse_eof_error) => parse_eof_error.state,
+            _ => self.parser.current_state(),
         }
     }
+
+    pub fn expected(&self) -> Parser::TokenSet {
+        self.parser.token_set_in_state(self.expected_state())
+    }
+
+    pub fn found(&self) -> Option<Token::Kind>
+    where
+        Token: Kinded,
+    {
+        self.parse_one_error.leftover_token().map(Kinded::kind)
+    }
 }
 
-impl<'source, NonTerminal, Token, StartSymbol, Prod, Tab, Ctx> Display
-    for ParseError<
-        Parser<NonTerminal, Token, StartSymbol, Prod, Tab, Ctx>,
-        NonTerminal,
-        Token,
-        Range<usize>,
-        &'source Token::Source,
-    >
+impl<'source, Parser, NonTerminal, Token> ToDiagnostic<'source>
+    for ParseError<Parser, NonTerminal, Token, Range<usize>, &'source Token::Source>
 where
+    Parser: ParserState,
     Token: Logos<'source, Source = str> + Display,
-    Tab: Tables<NonTerminal, Token, Prod>,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn to_diagnostic(&self) -> Diagnostic<'source> {
+        let expected_tokens = || {
+            format!(
+                "expected tokens are {}",
+                self.parser
+                    .tokens_in_state(self.expected_state())
+                    .iter()
+                    .format(", ")
+            )
+        };
+        // the snippets show `source` only
+        let local =
+            |span: &Range<usize>| span.start - self.source_offset..span.end - self.source_offset;
+        let limit_exceeded = |reason: LimitExceededReason, span: Range<usize>| {
+            Diagnostic::new(
+                "semasia::limit_exceeded",
+                reason,
+                "limit exceeded here",
+                span,
+                self.source,
+            )
+            .with_help("the limits can be raised with `Parser::with_limits`")
+        };
+        let eof = self.source.trim_end().len();
         match &self.parse_one_error {
             ParseOneError::ParseTokenError(parse_token_error) => match &parse_token_error.reason {
-                ParseTokenErrorReason::ActionNotFound { leftover_token } => {
-                    writeln!(
-                        f,
-                        "{}{}{}",
-                        "error".red().bold(),
-                        ": unexpected token ".bold(),
-                        leftover_token.to_string().bold(),
-                    )?;
-                    let (line, span, line_count) =
-                        to_line_span(self.source, parse_token_error.span.clone());
-                    let line_count_str = line_count.to_string();
-                    let line_count_len = line_count_str.len();
-                    writeln!(
-                        f,
-                        "{} {} {}",
-                        line_count_str.blue().bold(),
-                        "|".blue().bold(),
-                        line
-                    )?;
-                    writeln!(
-                        f,
-                        "{}{}{}{}",
-                        " ".repeat(line_count_len),
-                        " | ".blue().bold(),
-                        " ".repeat(span.start),
-                        "^".repeat(span.end - span.start).red().bold()
-                    )?;
-                    write!(
-                        f,
-                        "{}{}{}expected tokens are {}",
-                        " ".repeat(line_count_len),
-                        " = ".blue().bold(),
-                        "note: ".bold(),
-                        Tab::tokens_in_state(self.parser.stacks.current_state())
-                            .iter()
-                            .format(", "),
-                    )
-                }
+                ParseTokenErrorReason::ActionNotFound { leftover_token } => Diagnostic::new(
+                    "semasia::unexpected_token",
+                    format!("unexpected token {leftover_token}"),
+                    "unexpected token",
+                    local(&parse_token_error.span),
+                    self.source,
+                )
+                .with_found(leftover_token)
+                .with_expected(self.parser.tokens_in_state(self.expected_state()))
+                .with_note(expected_tokens()),
                 ParseTokenErrorReason::GotoNotFound {
                     leftover_non_terminal: _,
                 } => unreachable!("correctly reduced a production, but no goto action found"),
+                ParseTokenErrorReason::LimitExceeded {
+                    reason,
+                    leftover_token,
+                } => limit_exceeded(*reason, local(&parse_token_error.span))
+                    .with_found(leftover_token),
             },
             ParseOneError::ParseEofError(parse_eof_error) => match &parse_eof_error.reason {
-                ParseEofErrorReason::ActionNotFound => {
-                    writeln!(
-                        f,
-                        "{}{}",
-                        "error".red().bold(),
-                        ": unexpected end of source".bold(),
-                    )?;
-                    let (line, line_count) = last_line(self.source);
-                    let line_count_str = line_count.to_string();
-                    let line_count_len = line_count_str.len();
-                    writeln!(
-                        f,
-                        "{} {} {}",
-                        line_count_str.blue().bold(),
-                        "|".blue().bold(),
-                        line
-                    )?;
-                    writeln!(
-                        f,
-                        "{}{}{}{}",
-                        " ".repeat(line_count_len),
-                        " | ".blue().bold(),
-                        " ".repeat(line.len()),
-                        "^".red().bold()
-                    )?;
-                    write!(
-                        f,
-                        "{}{}{}expected tokens are {}",
-                        " ".repeat(line_count_len),
-                        " = ".blue().bold(),
-                        "note: ".bold(),
-                        Tab::tokens_in_state(self.parser.stacks.current_state())
-                            .iter()
-                            .format(", "),
-                    )
-                }
+                ParseEofErrorReason::ActionNotFound => Diagnostic::new(
+                    "semasia::unexpected_end_of_source",
+                    "unexpected end of source",
+                    "source ends here",
+                    eof..eof,
+                    self.source,
+                )
+                .with_expected(self.parser.tokens_in_state(self.expected_state()))
+                .with_note(expected_tokens()),
                 ParseEofErrorReason::GotoNotFound {
                     leftover_non_terminal: _,
                 } => unreachable!("correctly reduced a production, but no goto action found"),
+                ParseEofErrorReason::LimitExceeded(reason) => limit_exceeded(*reason, eof..eof),
             },
+            ParseOneError::LimitExceeded(limit_exceeded_error) => {
+                let diagnostic = limit_exceeded(
+                    limit_exceeded_error.reason,
+                    local(&limit_exceeded_error.span),
+                );
+                match &limit_exceeded_error.leftover_token {
+                    Some(leftover_token) => diagnostic.with_found(leftover_token),
+                    None => diagnostic,
+                }
+            }
         }
     }
 }
 
+impl<'source, Parser, NonTerminal, Token> Display
+    for ParseError<Parser, NonTerminal, Token, Range<usize>, &'source Token::Source>
+where
+    Parser: ParserState,
+    Token: Logos<'source, Source = str> + Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.to_diagnostic().render(f)
+    }
+}
+
 impl<Parser, NonTerminal, Token, Span, Source> Error
     for ParseError<Parser, NonTerminal, Token, Span, Source>
 where
@@ -238,12 +404,41 @@ where
 {
 }
 
+#[cfg(feature = "miette")]
+impl<'source, Parser, NonTerminal, Token> miette::Diagnostic
+    for ParseError<Parser, NonTerminal, Token, Range<usize>, &'source Token::Source>
+where
+    Parser: ParserState,
+    Token: Logos<'source, Source = str> + Display,
+    Self: Error,
+{
+    crate::diagnostic::miette_diagnostic_from_to_diagnostic!();
+}
+
+/// Points at `FileId::default()`, the file of a `SimpleFile`, `to_codespan` takes any other.
+#[cfg(feature = "codespan-reporting")]
+impl<'source, Parser, NonTerminal, Token, FileId: Default>
+    From<ParseError<Parser, NonTerminal, Token, Range<usize>, &'source Token::Source>>
+    for codespan_reporting::diagnostic::Diagnostic<FileId>
+where
+    Parser: ParserState,
+    Token: Logos<'source, Source = str> + Display,
+{
+    fn from(
+        error: ParseError<Parser, NonTerminal, Token, Range<usize>, &'source Token::Source>,
+    ) -> Self {
+        error.to_codespan(FileId::default())
+    }
+}
+
 #[derive(Debug)]
 pub struct LexError<'source, Parser, Token: Logos<'source>> {
     pub parser: Parser,
     pub lexer_error: Token::Error,
     pub span: Range<usize>,
     pub source: &'source Token::Source,
+    /// Where `source` starts in the whole input, which `span` is relative to.
+    pub source_offset: usize,
 }
 
 impl<'source, Parser, Token: Logos<'source>> LexError<'source, Parser, Token> {
@@ -258,60 +453,41 @@ impl<'source, Parser, Token: Logos<'source>> LexError<'source, Parser, Token> {
             lexer_error,
             span,
             source,
+            source_offset: 0,
         }
     }
-}
 
-impl<'source, Parser, Token: Logos<'source, Source = str>> Display
-    for LexError<'source, Parser, Token>
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(
-            f,
-            "{}{}",
-            "error".red().bold(),
-            ": unexpected character".bold()
-        )?;
-        let (line, span, line_count) = to_line_span(self.source, self.span.clone());
-        let line_count_str = line_count.to_string();
-        let line_count_len = line_count_str.len();
-        writeln!(
-            f,
-            "{} {} {}",
-            line_count_str.blue().bold(),
-            "|".blue().bold(),
-            line
-        )?;
-        write!(
-            f,
-            "{}{}{}{}",
-            " ".repeat(line_count_len),
-            " | ".blue().bold(),
-            " ".repeat(span.start),
-            "^".repeat(span.end - span.start).red().bold()
-        )
+    pub fn with_source_offset(mut self, source_offset: usize) -> Self {
+        self.source_offset = source_offset;
+        self
     }
 }
 
-fn to_line_span(source: &str, mut span: Range<usize>) -> (&str, Range<usize>, usize) {
-    for (line_count, line) in source.split('\n').enumerate() {
-        let line_len = line.len();
-        if span.start < line_len {
-            return (line, span, line_count + 1);
+impl<'source, Parser, Token: Logos<'source, Source = str>> ToDiagnostic<'source>
+    for LexError<'source, Parser, Token>
+{
+    fn to_diagnostic(&self) -> Diagnostic<'source> {
+        let span = self.span.start - self.source_offset..self.span.end - self.source_offset;
+        let diagnostic = Diagnostic::new(
+            "semasia::unexpected_character",
+            "unexpected character",
+            "unexpected character",
+            span.clone(),
+            self.source,
+        );
+        match self.source.get(span) {
+            Some(found) => diagnostic.with_found(found),
+            None => diagnostic,
         }
-        span.start -= line_len + 1;
-        span.end -= line_len + 1;
     }
-    unreachable!()
 }
 
-fn last_line(source: &str) -> (&str, usize) {
-    source
-        .split('\n')
-        .enumerate()
-        .last()
-        .map(|(line_count, line)| (line, line_count + 1))
-        .unwrap()
+impl<'source, Parser, Token: Logos<'source, Source = str>> Display
+    for LexError<'source, Parser, Token>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.to_diagnostic().render(f)
+    }
 }
 
 impl<'source, Parser, Token> Error for LexError<'source, Parser, Token>
@@ -321,6 +497,72 @@ where
 {
 }
 
+#[cfg(feature = "miette")]
+impl<'source, Parser, Token> miette::Diagnostic for LexError<'source, Parser, Token>
+where
+    Token: Logos<'source, Source = str>,
+    Self: Error,
+{
+    crate::diagnostic::miette_diagnostic_from_to_diagnostic!();
+}
+
+/// Points at `FileId::default()`, the file of a `SimpleFile`, `to_codespan` takes any other.
+#[cfg(feature = "codespan-reporting")]
+impl<'source, Parser, Token, FileId: Default> From<LexError<'source, Parser, Token>>
+    for codespan_reporting::diagnostic::Diagnostic<FileId>
+where
+    Token: Logos<'source, Source = str>,
+{
+    fn from(error: LexError<'source, Parser, Token>) -> Self {
+        error.to_codespan(FileId::default())
+    }
+}
+
+#[derive(Debug)]
+pub enum InputStatus<T, Expected, Error> {
+    Complete(T),
+    Incomplete { expected: Expected },
+    Invalid(Error),
+}
+
+impl<T, Expected, Error> InputStatus<T, Expected, Error> {
+    pub fn is_complete(&self) -> bool {
+        matches!(self, InputStatus::Complete(_))
+    }
+
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self, InputStatus::Incomplete { .. })
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, InputStatus::Invalid(_))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Completions<TokenSet> {
+    pub state: usize,
+    pub tokens: TokenSet,
+    pub token_names: &'static [&'static str],
+    pub non_terminals: &'static [&'static str],
+}
+
+#[derive(Debug)]
+pub struct Recovered<T, LexError> {
+    pub result: T,
+    pub lex_errors: Vec<LexError>,
+}
+
+impl<T, LexError> Recovered<T, LexError> {
+    pub fn new(result: T, lex_errors: Vec<LexError>) -> Self {
+        Self { result, lex_errors }
+    }
+
+    pub fn has_lex_errors(&self) -> bool {
+        !self.lex_errors.is_empty()
+    }
+}
+
 #[derive(Debug)]
 pub enum LexParseError<LexError, ParseError> {
     LexError(LexError),
@@ -332,7 +574,7 @@ where
     LexError: Display,
     ParseError: Display,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             LexParseError::LexError(lex_error) => write!(f, "{lex_error}"),
             LexParseError::ParseError(parse_error) => write!(f, "{parse_error}"),
@@ -340,4 +582,68 @@ where
     }
 }
 
+impl<'source, LexError, ParseError> ToDiagnostic<'source> for LexParseError<LexError, ParseError>
+where
+    LexError: ToDiagnostic<'source>,
+    ParseError: ToDiagnostic<'source>,
+{
+    fn to_diagnostic(&self) -> Diagnostic<'source> {
+        match self {
+            LexParseError::LexError(lex_error) => lex_error.to_diagnostic(),
+            LexParseError::ParseError(parse_error) => parse_error.to_diagnostic(),
+        }
+    }
+}
+
 impl<LexError, ParseError> Error for LexParseError<LexError, ParseError> where Self: Display + Debug {}
+
+#[cfg(feature = "miette")]
+impl<LexError, ParseError> LexParseError<LexError, ParseError>
+where
+    LexError: miette::Diagnostic,
+    ParseError: miette::Diagnostic,
+{
+    fn as_miette(&self) -> &dyn miette::Diagnostic {
+        match self {
+            LexParseError::LexError(lex_error) => lex_error,
+            LexParseError::ParseError(parse_error) => parse_error,
+        }
+    }
+}
+
+#[cfg(feature = "miette")]
+impl<LexError, ParseError> miette::Diagnostic for LexParseError<LexError, ParseError>
+where
+    LexError: miette::Diagnostic,
+    ParseError: miette::Diagnostic,
+    Self: Error,
+{
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.as_miette().code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.as_miette().help()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.as_miette().source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.as_miette().labels()
+    }
+}
+
+/// Points at `FileId::default()`, the file of a `SimpleFile`, `to_codespan` takes any other.
+#[cfg(feature = "codespan-reporting")]
+impl<'source, LexError, ParseError, FileId: Default> From<LexParseError<LexError, ParseError>>
+    for codespan_reporting::diagnostic::Diagnostic<FileId>
+where
+    LexError: ToDiagnostic<'source>,
+    ParseError: ToDiagnostic<'source>,
+{
+    fn from(error: LexParseError<LexError, ParseError>) -> Self {
+        error.to_codespan(FileId::default())
+    }
+}
@@ -0,0 +1,270 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Display, Write},
+    iter,
+    ops::Range,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{RenderMode, style::Style};
+
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceMap<'source> {
+    source: &'source str,
+    line_starts: Vec<usize>,
+    context_lines: usize,
+    render_mode: RenderMode,
+}
+
+impl<'source> SourceMap<'source> {
+    pub fn new(source: &'source str) -> Self {
+        let line_starts = iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+            context_lines: 1,
+            render_mode: RenderMode::Auto,
+        }
+    }
+
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    pub fn source(&self) -> &'source str {
+        self.source
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Zero-based index of the line containing the byte `offset`, offsets past the end
+    /// of the source belong to the last line.
+    pub fn line_index(&self, offset: usize) -> usize {
+        let offset = offset.min(self.source.len());
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    pub fn line(&self, index: usize) -> &'source str {
+        let start = self.line_starts[index];
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .map_or(self.source.len(), |&next_start| next_start - 1);
+        let line = &self.source[start..end];
+        line.strip_suffix('\r').unwrap_or(line)
+    }
+
+    pub fn location(&self, offset: usize) -> Location {
+        let index = self.line_index(offset);
+        Location {
+            line: index + 1,
+            column: self.column_in_line(index, offset) + 1,
+        }
+    }
+
+    pub fn write_snippet<W: Write + ?Sized>(&self, w: &mut W, span: Range<usize>) -> fmt::Result {
+        let (first_line, last_line) = self.span_lines(&span);
+        let (context_start, context_end) = self.context(first_line, last_line);
+        let gutter_width = gutter_width(context_end);
+        let colored = self.render_mode.is_colored();
+        for index in context_start..=context_end {
+            if index != context_start {
+                writeln!(w)?;
+            }
+            let line_number = (index + 1).to_string();
+            write!(
+                w,
+                "{}{} {} {}",
+                " ".repeat(gutter_width - line_number.len()),
+                Style::Gutter.paint(&line_number, colored),
+                Style::Gutter.paint("|", colored),
+                expand_tabs(self.line(index)),
+            )?;
+            if (first_line..=last_line).contains(&index) {
+                let start = if index == first_line {
+                    self.column_in_line(index, span.start)
+                } else {
+                    0
+                };
+                let end = if index == last_line {
+                    self.column_in_line(index, span.end)
+                } else {
+                    display_width(self.line(index))
+                };
+                write!(
+                    w,
+                    "\n{}{}{}{}",
+                    " ".repeat(gutter_width),
+                    Style::Gutter.paint(" | ", colored),
+                    " ".repeat(start),
+                    Style::Error.paint("^".repeat(end.saturating_sub(start).max(1)), colored)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_note<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        span: Range<usize>,
+        note: impl Display,
+    ) -> fmt::Result {
+        self.write_annotation(w, span, "note", note)
+    }
+
+    pub fn write_help<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        span: Range<usize>,
+        help: impl Display,
+    ) -> fmt::Result {
+        self.write_annotation(w, span, "help", help)
+    }
+
+    fn write_annotation<W: Write + ?Sized>(
+        &self,
+        w: &mut W,
+        span: Range<usize>,
+        kind: &str,
+        text: impl Display,
+    ) -> fmt::Result {
+        let (first_line, last_line) = self.span_lines(&span);
+        let (_, context_end) = self.context(first_line, last_line);
+        write!(
+            w,
+            "{}{}{}{}",
+            " ".repeat(gutter_width(context_end)),
+            Style::Gutter.paint(" = ", self.render_mode.is_colored()),
+            Style::Bold.paint(alloc::format!("{kind}: "), self.render_mode.is_colored()),
+            text
+        )
+    }
+
+    fn span_lines(&self, span: &Range<usize>) -> (usize, usize) {
+        let first_line = self.line_index(span.start);
+        let last_line = if span.end > span.start {
+            self.line_index(span.end - 1)
+        } else {
+            first_line
+        };
+        (first_line, last_line.max(first_line))
+    }
+
+    fn context(&self, first_line: usize, last_line: usize) -> (usize, usize) {
+        let is_blank = |index: usize| self.line(index).trim().is_empty();
+        let mut context_start = first_line.saturating_sub(self.context_lines);
+        while context_start < first_line && is_blank(context_start) {
+            context_start += 1;
+        }
+        let mut context_end = (last_line + self.context_lines).min(self.line_count() - 1);
+        while context_end > last_line && is_blank(context_end) {
+            context_end -= 1;
+        }
+        (context_start, context_end)
+    }
+
+    fn column_in_line(&self, index: usize, offset: usize) -> usize {
+        let line = self.line(index);
+        let mut offset_in_line = offset
+            .min(self.source.len())
+            .saturating_sub(self.line_starts[index])
+            .min(line.len());
+        while !line.is_char_boundary(offset_in_line) {
+            offset_in_line -= 1;
+        }
+        display_width(&line[..offset_in_line])
+    }
+}
+
+fn gutter_width(last_line_index: usize) -> usize {
+    (last_line_index + 1).to_string().len()
+}
+
+fn display_width(text: &str) -> usize {
+    text.graphemes(true).fold(0, |column, grapheme| {
+        if grapheme == "\t" {
+            column + TAB_WIDTH - column % TAB_WIDTH
+        } else {
+            column + grapheme.width()
+        }
+    })
+}
+
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for grapheme in line.graphemes(true) {
+        if grapheme == "\t" {
+            let width = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(iter::repeat_n(' ', width));
+            column += width;
+        } else {
+            expanded.push_str(grapheme);
+            column += grapheme.width();
+        }
+    }
+    expanded
+}
+
+#[test]
+fn source_map_test() {
+    let source = "let a = 1;\r\n\tb = \"日本\" + x;\nlast";
+    let source_map = SourceMap::new(source).render_mode(RenderMode::Plain);
+    assert_eq!(source_map.line_count(), 3);
+    assert_eq!(source_map.line(0), "let a = 1;");
+
+    // a tab reaches the next multiple of 4 and each of the CJK characters is two columns wide
+    let x = source.find('x').unwrap();
+    assert_eq!(
+        source_map.location(x),
+        Location {
+            line: 2,
+            column: 18
+        }
+    );
+    assert_eq!(
+        source_map.location(source.len()),
+        Location { line: 3, column: 5 }
+    );
+
+    let mut snippet = String::new();
+    let one = source.find('1').unwrap();
+    source_map
+        .write_snippet(&mut snippet, one..source.find('b').unwrap() + 1)
+        .unwrap();
+    assert_eq!(
+        snippet,
+        "1 | let a = 1;\n  |         ^^\n2 |     b = \"日本\" + x;\n  | ^^^^^\n3 | last"
+    );
+}
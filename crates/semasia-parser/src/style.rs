@@ -0,0 +1,67 @@
+use core::fmt::{self, Display};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Colored unless the `NO_COLOR` environment variable is set, always plain without `std`.
+    #[default]
+    Auto,
+    Colored,
+    Plain,
+}
+
+impl RenderMode {
+    pub fn is_colored(self) -> bool {
+        match self {
+            RenderMode::Auto => auto_colored(),
+            RenderMode::Colored => true,
+            RenderMode::Plain => false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn auto_colored() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty())
+}
+
+#[cfg(not(feature = "std"))]
+fn auto_colored() -> bool {
+    false
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Style {
+    Error,
+    Gutter,
+    Bold,
+}
+
+impl Style {
+    pub(crate) fn paint<T: Display>(self, text: T, colored: bool) -> Painted<T> {
+        Painted {
+            text,
+            style: self,
+            colored,
+        }
+    }
+}
+
+pub(crate) struct Painted<T> {
+    text: T,
+    style: Style,
+    colored: bool,
+}
+
+impl<T: Display> Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.colored {
+            return write!(f, "{}", self.text);
+        }
+        let code = match self.style {
+            Style::Error => "1;31",
+            Style::Gutter => "1;34",
+            Style::Bold => "1",
+        };
+        write!(f, "\x1b[{code}m{}\x1b[0m", self.text)
+    }
+}
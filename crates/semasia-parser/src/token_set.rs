@@ -0,0 +1,180 @@
+use core::{fmt::Display, marker::PhantomData};
+
+use itertools::Itertools;
+
+pub trait SymbolKind: Copy + 'static {
+    fn id(self) -> usize;
+    fn from_id(id: usize) -> Option<Self>;
+}
+
+pub trait Kinded {
+    type Kind: SymbolKind;
+
+    fn kind(&self) -> Self::Kind;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenSet<Kind, const WORDS: usize> {
+    bits: [u64; WORDS],
+    end_of_input: bool,
+    phantom_data: PhantomData<Kind>,
+}
+
+impl<Kind: SymbolKind, const WORDS: usize> TokenSet<Kind, WORDS> {
+    pub const fn new() -> Self {
+        Self::from_bits([0; WORDS], false)
+    }
+
+    pub const fn from_bits(bits: [u64; WORDS], end_of_input: bool) -> Self {
+        Self {
+            bits,
+            end_of_input,
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, kind: Kind) {
+        let id = kind.id();
+        self.bits[id / 64] |= 1 << (id % 64);
+    }
+
+    pub fn remove(&mut self, kind: Kind) {
+        let id = kind.id();
+        self.bits[id / 64] &= !(1 << (id % 64));
+    }
+
+    pub fn contains(&self, kind: Kind) -> bool {
+        let id = kind.id();
+        self.bits[id / 64] & (1 << (id % 64)) != 0
+    }
+
+    pub fn insert_end_of_input(&mut self) {
+        self.end_of_input = true;
+    }
+
+    pub fn contains_end_of_input(&self) -> bool {
+        self.end_of_input
+    }
+
+    /// The number of tokens, counting the end of input as one.
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum::<usize>()
+            + usize::from(self.end_of_input)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.end_of_input && self.bits.iter().all(|word| *word == 0)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut res = *self;
+        for (word, other_word) in res.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= other_word;
+        }
+        res.end_of_input |= other.end_of_input;
+        res
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Kind> + '_ {
+        self.bits
+            .iter()
+            .enumerate()
+            .flat_map(|(word_id, word)| {
+                (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_id * 64 + bit)
+            })
+            .filter_map(Kind::from_id)
+    }
+}
+
+impl<Kind: SymbolKind, const WORDS: usize> Default for TokenSet<Kind, WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Kind: SymbolKind, const WORDS: usize> FromIterator<Kind> for TokenSet<Kind, WORDS> {
+    fn from_iter<T: IntoIterator<Item = Kind>>(iter: T) -> Self {
+        let mut res = Self::new();
+        for kind in iter {
+            res.insert(kind);
+        }
+        res
+    }
+}
+
+impl<Kind, const WORDS: usize> core::fmt::Debug for TokenSet<Kind, WORDS>
+where
+    Kind: SymbolKind + core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut set = f.debug_set();
+        set.entries(self.iter());
+        if self.end_of_input {
+            set.entry(&"$");
+        }
+        set.finish()
+    }
+}
+
+impl<Kind, const WORDS: usize> Display for TokenSet<Kind, WORDS>
+where
+    Kind: SymbolKind + Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tokens = self.iter().peekable();
+        let any_token = tokens.peek().is_some();
+        write!(f, "{}", tokens.format(", "))?;
+        if self.end_of_input {
+            if any_token {
+                write!(f, ", ")?;
+            }
+            write!(f, "end of input")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn token_set_test() {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Kind(usize);
+
+    impl SymbolKind for Kind {
+        fn id(self) -> usize {
+            self.0
+        }
+
+        fn from_id(id: usize) -> Option<Self> {
+            Some(Self(id))
+        }
+    }
+
+    impl Display for Kind {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "t{}", self.0)
+        }
+    }
+
+    let empty = TokenSet::<Kind, 2>::new();
+    assert_eq!((empty.len(), empty.is_empty()), (0, true));
+    assert_eq!(alloc::format!("{empty}"), "");
+
+    let mut end_of_input = TokenSet::<Kind, 2>::new();
+    end_of_input.insert_end_of_input();
+    assert_eq!((end_of_input.len(), end_of_input.is_empty()), (1, false));
+    assert_eq!(alloc::format!("{end_of_input}"), "end of input");
+
+    let mut tokens = TokenSet::<Kind, 2>::from_iter([Kind(3), Kind(70)]);
+    tokens.insert_end_of_input();
+    assert_eq!((tokens.len(), tokens.is_empty()), (3, false));
+    assert_eq!(alloc::format!("{tokens}"), "t3, t70, end of input");
+    assert_eq!(
+        tokens.iter().collect::<alloc::vec::Vec<_>>(),
+        [Kind(3), Kind(70)]
+    );
+}
@@ -1,12 +1,104 @@
+use core::ops::Range;
+
+use logos::Logos;
+
 use crate::{EofAction, Stacks, TokenAction};
 
 pub trait Tables<NonTerminal, Token, Prod> {
+    type TokenSet;
+
     fn query_token_table(current_state: usize, current_token: &Token) -> Option<TokenAction<Prod>>;
     fn query_eof_table(current_state: usize) -> Option<EofAction<Prod>>;
+    /// The first reduction that `holds` among the ones guarded by a predicate in a conflict with
+    /// the action of the token table, tried in the order of the productions.
+    fn query_predicated_token_table(
+        _current_state: usize,
+        _current_token: &Token,
+        _holds: impl FnMut(&Prod) -> bool,
+    ) -> Option<Prod> {
+        None
+    }
+    fn query_predicated_eof_table(
+        _current_state: usize,
+        _holds: impl FnMut(&Prod) -> bool,
+    ) -> Option<Prod> {
+        None
+    }
+    /// Another token the text at `span` could be lexed as, the first one that `accepts` takes.
+    fn reinterpret_token<'source>(
+        _current_token: &Token,
+        _source: &'source Token::Source,
+        _span: Range<usize>,
+        _accepts: impl FnMut(&Token) -> bool,
+    ) -> Option<Token>
+    where
+        Token: Logos<'source>,
+    {
+        None
+    }
     fn query_goto_table(current_state: usize, non_terminal: &NonTerminal) -> Option<usize>;
+    fn query_goto_table_by_id(current_state: usize, non_terminal_id: usize) -> Option<usize>;
+    fn production_arity(production: &Prod) -> usize;
+    fn production_head(production: &Prod) -> usize;
     fn tokens_in_state(current_state: usize) -> &'static [&'static str];
+    fn token_set_in_state(current_state: usize) -> Self::TokenSet;
+    fn non_terminals_in_state(current_state: usize) -> &'static [&'static str];
+}
+
+pub trait EntryPoint<NonTerminal, StartSymbol> {
+    const INITIAL_STATE: usize;
+
+    fn accept(non_terminal: NonTerminal) -> StartSymbol;
 }
 
 pub trait Reduce<NonTerminal, Token, Ctx> {
     fn reduce(&self, ctx: &mut Ctx, stacks: &mut Stacks<NonTerminal, Token>) -> NonTerminal;
+    fn predicate(&self, _ctx: &Ctx, _lookahead: Option<&Token>) -> bool {
+        true
+    }
+    /// Runs on a token every time it's about to be parsed, it can replace it with another one.
+    /// A token that triggers reductions is parsed again after each of them, with what this
+    /// returned the previous time, so it has to be idempotent.
+    fn reclassify(_ctx: &mut Ctx, token: Token) -> Token {
+        token
+    }
+}
+
+/// The value of a token out of what its lexer callback returns.
+pub trait CallbackOutput<T> {
+    fn into_value(self) -> Option<T>;
+}
+
+impl<T> CallbackOutput<T> for T {
+    fn into_value(self) -> Option<T> {
+        Some(self)
+    }
+}
+
+impl<T> CallbackOutput<T> for Option<T> {
+    fn into_value(self) -> Option<T> {
+        self
+    }
+}
+
+impl<T, E> CallbackOutput<T> for Result<T, E> {
+    fn into_value(self) -> Option<T> {
+        self.ok()
+    }
+}
+
+pub trait ParserState {
+    type TokenSet;
+
+    fn current_state(&self) -> usize;
+    fn tokens_in_state(&self, state: usize) -> &'static [&'static str];
+    fn token_set_in_state(&self, state: usize) -> Self::TokenSet;
+
+    fn expected_tokens(&self) -> &'static [&'static str] {
+        self.tokens_in_state(self.current_state())
+    }
+
+    fn expected_token_set(&self) -> Self::TokenSet {
+        self.token_set_in_state(self.current_state())
+    }
 }
@@ -1,58 +1,187 @@
+use std::fmt::Display;
+
 use crate::{
-    chess_move::Move,
+    chess_move::{Castling, Move, SimpleMove},
     piece::{Color, Piece, PieceType},
 };
 
-#[derive(Default)]
+type Square = (usize, usize);
+
 pub struct Board {
     board: [[Option<Piece>; 8]; 8],
+    to_move: Color,
 }
 
 impl Board {
     pub fn empty() -> Self {
         Self {
             board: [[None; 8]; 8],
+            to_move: Color::White,
         }
     }
 
     pub fn starting_board() -> Self {
+        let back_rank = [
+            PieceType::Rook,
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Queen,
+            PieceType::King,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Rook,
+        ];
         Self {
             board: [
-                [
-                    PieceType::Rook,
-                    PieceType::Knight,
-                    PieceType::Bishop,
-                    PieceType::Queen,
-                    PieceType::King,
-                    PieceType::Bishop,
-                    PieceType::Knight,
-                    PieceType::Rook,
-                ]
-                .map(|pice_type| Some(Piece::new(Color::White, pice_type))),
+                back_rank.map(|piece_type| Some(Piece::new(Color::White, piece_type))),
                 [PieceType::Pawn; 8].map(|piece_type| Some(Piece::new(Color::White, piece_type))),
                 [None; 8],
                 [None; 8],
                 [None; 8],
                 [None; 8],
                 [PieceType::Pawn; 8].map(|piece_type| Some(Piece::new(Color::Black, piece_type))),
-                [
-                    PieceType::Rook,
-                    PieceType::Knight,
-                    PieceType::Bishop,
-                    PieceType::Queen,
-                    PieceType::King,
-                    PieceType::Bishop,
-                    PieceType::Knight,
-                    PieceType::Rook,
-                ]
-                .map(|pice_type| Some(Piece::new(Color::Black, pice_type))),
+                back_rank.map(|piece_type| Some(Piece::new(Color::Black, piece_type))),
             ],
+            to_move: Color::White,
+        }
+    }
+
+    pub fn pieces(&self) -> usize {
+        self.board.iter().flatten().flatten().count()
+    }
+
+    /// Plays a move of the side to move, moves that no piece can make are skipped.
+    pub fn do_move(&mut self, chess_move: &Move) {
+        let color = self.to_move;
+        match chess_move {
+            Move::SimpleMove(simple_move) => self.do_simple_move(color, simple_move),
+            Move::Castling(castling, _) => {
+                let rank = match color {
+                    Color::White => 0,
+                    Color::Black => 7,
+                };
+                let (king_file, rook_file, rook_target) = match castling {
+                    Castling::KingSide => (6, 7, 5),
+                    Castling::QueenSide => (2, 0, 3),
+                };
+                self.board[rank][king_file] = self.board[rank][4].take();
+                self.board[rank][rook_target] = self.board[rank][rook_file].take();
+            }
+        }
+        self.to_move = color.opponent();
+    }
+
+    fn do_simple_move(&mut self, color: Color, simple_move: &SimpleMove) {
+        let target = simple_move.target.tile.coordinates();
+        let source_file = simple_move
+            .target
+            .source_file
+            .map(|file| file as usize - 'a' as usize);
+        let source = (0..8)
+            .flat_map(|rank| (0..8).map(move |file| (rank, file)))
+            .find(|&(rank, file)| {
+                self.board[rank][file].is_some_and(|piece| {
+                    piece.color() == color && piece.piece_type() == simple_move.piece_type
+                }) && source_file.is_none_or(|source_file| source_file == file)
+                    && self.reaches(
+                        color,
+                        simple_move.piece_type,
+                        (rank, file),
+                        target,
+                        simple_move.target.takes,
+                    )
+            });
+        let Some((rank, file)) = source else {
+            return;
+        };
+        let piece = self.board[rank][file].take();
+        self.board[target.0][target.1] = match simple_move.promotion_piece_type {
+            Some(piece_type) => Some(Piece::new(color, piece_type)),
+            None => piece,
+        };
+    }
+
+    fn reaches(
+        &self,
+        color: Color,
+        piece_type: PieceType,
+        from: Square,
+        to: Square,
+        takes: bool,
+    ) -> bool {
+        let ranks = to.0 as isize - from.0 as isize;
+        let files = to.1 as isize - from.1 as isize;
+        match piece_type {
+            PieceType::Pawn => {
+                let (forward, start) = match color {
+                    Color::White => (1, 1),
+                    Color::Black => (-1, 6),
+                };
+                if takes {
+                    ranks == forward && files.abs() == 1
+                } else {
+                    files == 0
+                        && (ranks == forward
+                            || (ranks == 2 * forward
+                                && from.0 == start
+                                && self.clear_path(from, to)))
+                }
+            }
+            PieceType::Knight => matches!((ranks.abs(), files.abs()), (1, 2) | (2, 1)),
+            PieceType::King => ranks.abs() <= 1 && files.abs() <= 1,
+            PieceType::Rook => (ranks == 0 || files == 0) && self.clear_path(from, to),
+            PieceType::Bishop => ranks.abs() == files.abs() && self.clear_path(from, to),
+            PieceType::Queen => {
+                (ranks == 0 || files == 0 || ranks.abs() == files.abs())
+                    && self.clear_path(from, to)
+            }
         }
     }
 
-    pub fn do_move(&mut self, chess_move: Move) {}
+    /// Whether the squares strictly between `from` and `to` on a line are empty.
+    fn clear_path(&self, from: Square, to: Square) -> bool {
+        let step = (
+            (to.0 as isize - from.0 as isize).signum(),
+            (to.1 as isize - from.1 as isize).signum(),
+        );
+        let mut square = from;
+        loop {
+            square = (
+                (square.0 as isize + step.0) as usize,
+                (square.1 as isize + step.1) as usize,
+            );
+            if square == to {
+                return true;
+            }
+            if self.board[square.0][square.1].is_some() {
+                return false;
+            }
+        }
+    }
+}
 
-    pub fn best_move(&mut self) -> Move {
-        todo!()
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rank in self.board.iter().rev() {
+            for square in rank {
+                let symbol = square.map_or('.', |piece| {
+                    let symbol = match piece.piece_type() {
+                        PieceType::Pawn => 'P',
+                        PieceType::Bishop => 'B',
+                        PieceType::Knight => 'N',
+                        PieceType::Rook => 'R',
+                        PieceType::Queen => 'Q',
+                        PieceType::King => 'K',
+                    };
+                    match piece.color() {
+                        Color::White => symbol,
+                        Color::Black => symbol.to_ascii_lowercase(),
+                    }
+                });
+                write!(f, "{symbol}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
     }
 }
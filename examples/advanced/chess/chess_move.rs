@@ -2,65 +2,63 @@ use std::fmt::Display;
 
 use crate::{piece::PieceType, tile::Tile};
 
-pub struct SimpleMove {
-    pub piece_type: PieceType,
-    pub target_tile: Tile,
+pub struct Target {
     pub source_file: Option<char>,
-    pub source_rank: Option<usize>,
     pub takes: bool,
-    pub check: bool,
-    pub checkmate: bool,
-    pub promotion_piece_type: Option<PieceType>,
+    pub tile: Tile,
 }
 
-impl SimpleMove {
-    pub fn piece_moved(piece_type: PieceType, target_tile: Tile) -> Self {
-        Self {
-            piece_type,
-            target_tile,
-            source_file: None,
-            source_rank: None,
-            takes: false,
-            check: false,
-            checkmate: false,
-            promotion_piece_type: None,
-        }
-    }
-
-    pub fn takes(piece_type: PieceType, target_tile: Tile) -> Self {
+impl Target {
+    pub fn new(source_file: Option<char>, takes: bool, tile: Tile) -> Self {
         Self {
-            piece_type,
-            target_tile,
-            source_file: None,
-            source_rank: None,
-            takes: true,
-            check: false,
-            checkmate: false,
-            promotion_piece_type: None,
+            source_file,
+            takes,
+            tile,
         }
     }
 }
 
-impl Display for SimpleMove {
+impl Display for Target {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.piece_type)?;
-
-        if let Some(ref file) = self.source_file {
+        if let Some(file) = self.source_file {
             write!(f, "{file}")?;
         }
-        if let Some(ref rank) = self.source_rank {
-            write!(f, "{rank}")?;
-        }
         if self.takes {
             write!(f, "x")?;
         }
-        if let Some(ref prom) = self.promotion_piece_type {
-            write!(f, "{prom}")?;
+        write!(f, "{}", self.tile)
+    }
+}
+
+pub enum CheckSign {
+    Check,
+    CheckMate,
+}
+
+impl Display for CheckSign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Check => write!(f, "+"),
+            Self::CheckMate => write!(f, "#"),
+        }
+    }
+}
+
+pub struct SimpleMove {
+    pub piece_type: PieceType,
+    pub target: Target,
+    pub promotion_piece_type: Option<PieceType>,
+    pub check: Option<CheckSign>,
+}
+
+impl Display for SimpleMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.piece_type, self.target)?;
+        if let Some(prom) = self.promotion_piece_type {
+            write!(f, "={prom}")?;
         }
-        if self.check {
-            write!(f, "+")?;
-        } else if self.checkmate {
-            write!(f, "#")?;
+        if let Some(check) = &self.check {
+            write!(f, "{check}")?;
         }
         Ok(())
     }
@@ -82,14 +80,15 @@ impl Display for Castling {
 
 pub enum Move {
     SimpleMove(SimpleMove),
-    Castling(Castling),
+    Castling(Castling, Option<CheckSign>),
 }
 
 impl Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::SimpleMove(mv) => write!(f, "{mv}"),
-            Self::Castling(ca) => write!(f, "{ca}"),
+            Self::Castling(ca, None) => write!(f, "{ca}"),
+            Self::Castling(ca, Some(check)) => write!(f, "{ca}{check}"),
         }
     }
 }
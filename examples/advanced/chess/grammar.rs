@@ -1,6 +1,4 @@
-#![semasia::grammar]
-
-use std::str::FromStr;
+#![logos(skip r"\s+")]
 
 use semasia::*;
 
@@ -9,32 +7,34 @@ use crate::board::Board;
 
 #[non_terminal]
 #[start_symbol]
-pub enum Game {
-    WhiteWon,
-    BlackWon,
-    StaleMate,
-}
+pub type Game = Vec<Move>;
 
 #[non_terminal]
 pub type SetupString = ();
 
 #[non_terminal]
-pub type Moves = ();
+pub type Moves = Vec<Move>;
+
+#[non_terminal]
+pub use crate::chess_move::SimpleMove;
+
+#[non_terminal]
+pub use crate::chess_move::Castling;
 
 #[non_terminal]
-use crate::chess_move::SimpleMove;
+pub use crate::chess_move::Move;
 
 #[non_terminal]
-use crate::chess_move::Castling;
+pub use crate::chess_move::Target;
 
 #[non_terminal]
-pub use chess_move::Move;
+pub use crate::chess_move::CheckSign;
 
 #[non_terminal]
-pub use piece::PieceType;
+pub use crate::piece::PieceType;
 
 #[non_terminal]
-pub use tile::Tile;
+pub use crate::tile::Tile;
 
 #[token("K")]
 pub struct KingPiece;
@@ -63,61 +63,72 @@ pub struct CheckMate;
 #[token("=")]
 pub struct Promotes;
 
-#[token(regex = "0-0|O-O")]
+#[regex("0-0|O-O")]
 pub struct KingSideCastling;
 
-#[token(regex = "0-0-0|O-O-O")]
+#[regex("0-0-0|O-O-O")]
 pub struct QueenSideCastling;
 
-#[token(regex = "[a-h]")]
+#[regex("[a-h]", |lex| lex.slice().chars().next())]
 pub type File = char;
 
-#[token(regex = "[1-8]")]
+#[regex("[1-8]", parse)]
 pub type Rank = usize;
 
 // GAME
-production!(G, Game -> (SetupString, Moves), |board, _| {
-    todo!()
-});
+production!(G: Game -> (SetupString, Moves), |(_, moves)| moves);
 
 // SETUP STRING
-production!(S0, SetupString -> (), |board, _| *board = Board::starting_board());
+production!(S0: SetupString -> (), |board, _| *board = Board::starting_board());
 
 // MOVES
-production!(M0, Game -> Moves, |_| todo!());
-production!(M1, Moves -> (Moves, Move), |board, (_, mv)| {
-    board.do_move(mv);
-    let response = board.best_move();
-    println!("{response}");
-    board.do_move(response);
+production!(M0: Moves -> (Moves, Move), |board, (mut moves, mv)| {
+    board.do_move(&mv);
+    moves.push(mv);
+    moves
 });
-production!(M2, Moves -> ());
-production!(M3, Move -> SimpleMove, |sm| Move::SimpleMove(sm));
-production!(M4, Move -> Castling, |c| Move::Castling(c));
-
-// MOVE
-// production!(M5, SimpleMove -> (PieceType, Tile), |(ty, pos)| SimpleMove::piece_moved(ty, pos));
-// production!(M7, SimpleMove -> (PieceType, Takes, Tile), |(ty, _, pos)| SimpleMove::takes(ty, pos));
-// production!(M8, SimpleMove -> (PieceType, File, Tile), |(ty, sf, pos)| todo!());
+production!(M1: Moves -> (), |_| Vec::new());
+production!(M2: Move -> SimpleMove, |simple_move| Move::SimpleMove(simple_move));
+ebnf!(M3: Move -> (Castling, Option<CheckSign>), |(castling, check)| Move::Castling(castling, check));
+
+// SIMPLE MOVE
+ebnf!(
+    PieceMove: SimpleMove -> (PieceType, Target, Option<CheckSign>),
+    |(piece_type, target, check)| SimpleMove {
+        piece_type,
+        target,
+        promotion_piece_type: None,
+        check,
+    }
+);
 ebnf!(
-    SimpleMoveProd,
-    SimpleMove -> (
-        PieceType,
-        File?,
-        Rank?,
-        Takes?,
-        Tile,
-        (
-            Promotes,
-            PieceType
-        )?,
-        CheckType { Check, CheckMate }
-    ), |string| todo!());
+    PawnMove: SimpleMove -> (Target, Option<(Promotes, PieceType)>, Option<CheckSign>),
+    |(target, promotion, check)| SimpleMove {
+        piece_type: PieceType::Pawn,
+        target,
+        promotion_piece_type: promotion.map(|(_, piece_type)| piece_type),
+        check,
+    }
+);
+
+// TARGET
+production!(T0: Target -> Tile, |tile| Target::new(None, false, tile));
+production!(T1: Target -> (Takes, Tile), |(_, tile)| Target::new(None, true, tile));
+production!(T2: Target -> (File, Tile), |(file, tile)| Target::new(Some(file), false, tile));
+production!(T3: Target -> (File, Takes, Tile), |(file, _, tile)| Target::new(Some(file), true, tile));
+production!(T4: Tile -> (File, Rank), |(file, rank)| Tile::new(file, rank));
+
+// CASTLING
+production!(C0: Castling -> KingSideCastling, |_| Castling::KingSide);
+production!(C1: Castling -> QueenSideCastling, |_| Castling::QueenSide);
+
+// CHECKS
+production!(K0: CheckSign -> Check, |_| CheckSign::Check);
+production!(K1: CheckSign -> CheckMate, |_| CheckSign::CheckMate);
 
 // PIECE TYPES
-production!(P0, PieceType -> KingPiece, |_| PieceType::King);
-production!(P1, PieceType -> QueenPiece, |_| PieceType::Queen);
-production!(P2, PieceType -> RookPiece, |_| PieceType::Rook);
-production!(P3, PieceType -> BishopPiece, |_| PieceType::Bishop);
-production!(P4, PieceType -> KnightPiece, |_| PieceType::Knight);
-production!(P5, PieceType -> (), |_| PieceType::Pawn);
+production!(P0: PieceType -> KingPiece, |_| PieceType::King);
+production!(P1: PieceType -> QueenPiece, |_| PieceType::Queen);
+production!(P2: PieceType -> RookPiece, |_| PieceType::Rook);
+production!(P3: PieceType -> BishopPiece, |_| PieceType::Bishop);
+production!(P4: PieceType -> KnightPiece, |_| PieceType::Knight);
@@ -1,7 +1,25 @@
 mod board;
 mod chess_move;
-mod grammar;
+#[semasia::grammar(path = "grammar.rs")]
+mod grammar {}
 mod piece;
 mod tile;
 
-fn main() {}
+use board::Board;
+
+fn main() {
+    let res = grammar::Parser::lex_parse_with_ctx(
+        Board::empty(),
+        "e4 e5 Nf3 Nc6 Bb5 a6 Bxc6 dxc6 0-0 f6 d4 exd4 Nxd4 c5 Nb3 Qxd1 Rxd1",
+    );
+    match res {
+        Ok((moves, board)) => {
+            for (turn, pair) in moves.chunks(2).enumerate() {
+                let black = pair.get(1).map(ToString::to_string).unwrap_or_default();
+                println!("{}. {} {black}", turn + 1, pair[0]);
+            }
+            println!("{} pieces left\n{board}", board.pieces());
+        }
+        Err(err) => println!("{err}"),
+    }
+}
@@ -1,12 +1,21 @@
 use std::fmt::Display;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Color {
     Black,
     White,
 }
 
-#[derive(Clone, Copy)]
+impl Color {
+    pub fn opponent(self) -> Self {
+        match self {
+            Color::Black => Color::White,
+            Color::White => Color::Black,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum PieceType {
     Pawn,
     Bishop,
@@ -39,4 +48,12 @@ impl Piece {
     pub fn new(color: Color, piece_type: PieceType) -> Self {
         Self { color, piece_type }
     }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn piece_type(&self) -> PieceType {
+        self.piece_type
+    }
 }
@@ -1,4 +1,22 @@
+use std::fmt::Display;
+
 pub struct Tile {
     file: char,
     rank: usize,
 }
+
+impl Tile {
+    pub fn new(file: char, rank: usize) -> Self {
+        Self { file, rank }
+    }
+
+    pub fn coordinates(&self) -> (usize, usize) {
+        (self.rank - 1, self.file as usize - 'a' as usize)
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.file, self.rank)
+    }
+}
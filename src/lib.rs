@@ -1,4 +1,5 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 pub use semasia_ebnf_proc_macro::*;
 pub use semasia_from_inherited::*;
@@ -0,0 +1,46 @@
+use semasia::*;
+
+mod ast {
+    #[derive(Debug, PartialEq)]
+    pub struct Item(pub usize);
+}
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod program {
+    use super::*;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Program = (Vec<ast::Item>, usize);
+
+    #[non_terminal]
+    pub type Items = Vec<ast::Item>;
+
+    #[non_terminal]
+    pub use crate::ast::Item;
+
+    #[regex(r"\d+", parse)]
+    pub type Number = usize;
+
+    #[token(";")]
+    pub struct Semicolon;
+
+    #[token("#")]
+    pub struct Hash;
+
+    production!(WithCount: Program -> (Vec<ast::Item>, Hash, Number), |(items, _, count)| (items, count));
+    production!(MoreItems: Items -> (Vec<ast::Item>, crate::ast::Item, Semicolon), |(mut items, item, _)| {
+        items.push(item);
+        items
+    });
+    production!(NoItems: Items -> (), |_| Vec::new());
+    production!(AnItem: Item -> self::Number, |n| ast::Item(n));
+}
+
+#[test]
+fn body_elements_are_resolved_by_path() {
+    let (items, count) = program::Parser::lex_parse("1; 2; 3; # 3").ok().unwrap();
+    assert_eq!(items, vec![ast::Item(1), ast::Item(2), ast::Item(3)]);
+    assert_eq!(count, 3);
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::arithmetic::*;
+
+#[test]
+fn completions_after_the_cursor() {
+    let mut parser = Parser::new();
+    let completions = parser.completions_at("1 + ", 4).unwrap();
+    assert!(completions.tokens.contains(TokenKind::Number));
+    assert!(completions.tokens.contains(TokenKind::OpenPar));
+    assert!(!completions.tokens.contains_end_of_input());
+    assert_eq!(completions.token_names, ["a number", "'('"]);
+    assert_eq!(completions.non_terminals, ["Term"]);
+
+    // only the tokens that end before the cursor count
+    let completions = parser.completions_at("(1 + 2) * 3", 1).unwrap();
+    assert_eq!(completions.non_terminals, ["Expression"]);
+}
+
+#[test]
+fn no_completions_after_invalid_input() {
+    assert!(Parser::new().completions_at("1 + ) ", 6).is_none());
+}
+
+#[test]
+fn completions_stop_at_lex_errors() {
+    // the tokens after `$` are not stepped through
+    let completions = Parser::new().completions_at("1 + $ (", 7).unwrap();
+    assert_eq!(completions.non_terminals, ["Term"]);
+}
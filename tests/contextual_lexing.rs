@@ -0,0 +1,112 @@
+use semasia::*;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod decls {
+    use super::*;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Stmts = Vec<String>;
+
+    #[non_terminal]
+    pub type Stmt = String;
+
+    #[regex(r"[a-z]+", to_string)]
+    pub type Ident = String;
+
+    #[token("type")]
+    pub struct Type;
+
+    #[token("=")]
+    pub struct Eq;
+
+    #[token(";")]
+    pub struct Semi;
+
+    production!(Empty: Stmts -> (), |_| Vec::new());
+    production!(More: Stmts -> (Stmts, Stmt), |(mut stmts, stmt)| { stmts.push(stmt); stmts });
+    production!(Declare: Stmt -> (Type, Ident, Semi), |(_, name, _)| format!("type {name}"));
+    production!(Assign: Stmt -> (Ident, Eq, Ident, Semi), |(lhs, _, rhs, _)| format!("{lhs} = {rhs}"));
+}
+
+#[test]
+fn keywords_are_lexed_again_where_identifiers_are_expected() {
+    let (stmts, ()) = decls::Parser::new()
+        .with_contextual_lexing(true)
+        .do_lex_parse("type type; y = type; type x;")
+        .ok()
+        .unwrap();
+    assert_eq!(stmts, vec!["type type", "y = type", "type x"]);
+}
+
+#[test]
+fn keywords_stay_keywords_without_contextual_lexing() {
+    assert!(decls::Parser::lex_parse("type type;").is_err());
+    assert_eq!(
+        decls::Parser::lex_parse("type x; y = x;").ok(),
+        Some(vec!["type x".to_string(), "y = x".to_string()])
+    );
+}
+
+#[test]
+fn check_source_lexes_contextually() {
+    let mut parser = decls::Parser::new().with_contextual_lexing(true);
+    assert!(parser.check_source("y = type;").is_complete());
+    assert!(!decls::Parser::new().check_source("y = type;").is_complete());
+}
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod colors {
+    use super::*;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Stmts = Vec<String>;
+
+    #[non_terminal]
+    pub type Stmt = String;
+
+    #[regex(r"[a-z]+", to_string)]
+    pub type Ident = String;
+
+    #[regex(r"[0-9a-f]+", |lex| usize::from_str_radix(lex.slice(), 16).ok(), priority = 3)]
+    pub type Hex = usize;
+
+    #[token("=")]
+    pub struct Eq;
+
+    #[token("#")]
+    pub struct Hash;
+
+    #[token(";")]
+    pub struct Semi;
+
+    production!(Empty: Stmts -> (), |_| Vec::new());
+    production!(More: Stmts -> (Stmts, Stmt), |(mut stmts, stmt)| { stmts.push(stmt); stmts });
+    production!(Assign: Stmt -> (Ident, Eq, Ident, Semi), |(lhs, _, rhs, _)| format!("{lhs} = {rhs}"));
+    production!(Color: Stmt -> (Ident, Eq, Hash, Hex, Semi), |(lhs, _, _, hex, _)| format!("{lhs} = {hex}"));
+}
+
+#[test]
+fn regexes_are_lexed_again_as_other_regexes() {
+    let (stmts, ()) = colors::Parser::new()
+        .with_contextual_lexing(true)
+        .do_lex_parse("bg = #beef; fg = bg; face = dead;")
+        .ok()
+        .unwrap();
+    assert_eq!(stmts, vec!["bg = 48879", "fg = bg", "face = dead"]);
+    assert!(colors::Parser::lex_parse("face = dead;").is_err());
+}
+
+#[test]
+fn regexes_are_lexed_again_over_the_whole_token() {
+    // `[a-z]+` only takes `abc` out of `abc12`, so it stays a `Hex`
+    assert!(
+        colors::Parser::new()
+            .with_contextual_lexing(true)
+            .do_lex_parse("x = abc12;")
+            .is_err()
+    );
+}
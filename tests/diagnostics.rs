@@ -0,0 +1,106 @@
+mod common;
+
+use common::arithmetic::*;
+use semasia_parser::{Location, RenderMode, ToDiagnostic, results::LexParseError};
+
+#[test]
+fn errors_convert_to_diagnostics() {
+    let source = "1 +\n(2 * )";
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse(source) else {
+        panic!("should fail to parse");
+    };
+    let diagnostic = error.to_diagnostic();
+    assert_eq!(diagnostic.code, "semasia::unexpected_token");
+    assert_eq!(diagnostic.label, "unexpected token");
+    assert_eq!(diagnostic.span, 9..10);
+    assert_eq!(diagnostic.found.as_deref(), Some("')'"));
+    assert_eq!(diagnostic.into_owned().source, source);
+
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("(1 + 2  \n") else {
+        panic!("should fail to parse");
+    };
+    let diagnostic = error.to_diagnostic();
+    assert_eq!(diagnostic.code, "semasia::unexpected_end_of_source");
+    assert_eq!(diagnostic.span, 6..6);
+    assert_eq!(diagnostic.found, None);
+}
+
+#[test]
+fn errors_render_as_plain_text_and_structured_data() {
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 +\n(2 * )") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(
+        error.render(RenderMode::Plain),
+        "error: unexpected token ')'\n\
+         1 | 1 +\n\
+         2 | (2 * )\n\
+         \x20 |      ^\n\
+         \x20 = note: expected tokens are a number, '('"
+    );
+    let structured = error.to_structured();
+    assert_eq!(structured.kind, "semasia::unexpected_token");
+    assert_eq!(structured.start, Location { line: 2, column: 6 });
+    assert_eq!(structured.end, Location { line: 2, column: 7 });
+    assert_eq!(structured.expected, ["a number", "'('"]);
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn errors_are_miette_diagnostics() {
+    use miette::Diagnostic;
+
+    let error = Parser::lex_parse("1 +\n(2 * )").unwrap_err();
+    assert_eq!(
+        error.code().map(|code| code.to_string()).as_deref(),
+        Some("semasia::unexpected_token")
+    );
+    assert_eq!(
+        error.help().map(|help| help.to_string()).as_deref(),
+        Some("expected tokens are a number, '('")
+    );
+    assert!(error.source_code().is_some());
+    let labels = error.labels().unwrap().collect::<Vec<_>>();
+    assert_eq!(labels.len(), 1);
+    assert_eq!((labels[0].offset(), labels[0].len()), (9, 1));
+    assert_eq!(labels[0].label(), Some("unexpected token"));
+
+    let Err(LexParseError::LexError(error)) = Parser::lex_parse("1 + a") else {
+        panic!("should fail to lex");
+    };
+    assert_eq!(
+        error.code().map(|code| code.to_string()).as_deref(),
+        Some("semasia::unexpected_character")
+    );
+    assert_eq!(error.labels().unwrap().next().unwrap().offset(), 4);
+
+    fn parse(source: &'static str) -> miette::Result<usize> {
+        Ok(Parser::lex_parse(source)?)
+    }
+    assert!(parse("1 + (2 *").is_err());
+}
+
+#[cfg(feature = "codespan-reporting")]
+#[test]
+fn errors_convert_to_codespan_diagnostics() {
+    use codespan_reporting::diagnostic::Diagnostic;
+
+    let diagnostic: Diagnostic<()> = Parser::lex_parse("1 + a").unwrap_err().into();
+    assert_eq!(
+        diagnostic.code.as_deref(),
+        Some("semasia::unexpected_character")
+    );
+    assert_eq!(diagnostic.labels[0].range, 4..5);
+
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 +\n(2 * )") else {
+        panic!("should fail to parse");
+    };
+    let diagnostic = error.to_codespan(3);
+    assert_eq!(diagnostic.labels[0].file_id, 3);
+    assert_eq!(diagnostic.labels[0].range, 9..10);
+    let diagnostic: Diagnostic<()> = error.into();
+    assert_eq!(
+        diagnostic.code.as_deref(),
+        Some("semasia::unexpected_token")
+    );
+}
@@ -0,0 +1,60 @@
+use semasia::*;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod statements {
+    use super::*;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Program = Vec<usize>;
+
+    #[entry_point]
+    #[non_terminal]
+    pub type Expression = usize;
+
+    #[entry_point]
+    #[non_terminal]
+    pub type Factor = usize;
+
+    #[regex(r"\d+", parse)]
+    pub type Number = usize;
+
+    #[token("+")]
+    pub struct Plus;
+
+    #[token(";")]
+    pub struct Semicolon;
+
+    production!(Statement: Program -> (Program, Expression, Semicolon), |(mut p, e, _)| {
+        p.push(e);
+        p
+    });
+    production!(EmptyProgram: Program -> (), |_| Vec::new());
+    production!(Addition: Expression -> (Expression, Plus, Factor), |(e, _, f)| e + f);
+    production!(NoAddition: Expression -> Factor);
+    production!(ActualNumber: Factor -> Number);
+}
+
+#[test]
+fn start_symbol_is_still_parsed() {
+    assert_eq!(
+        statements::Parser::lex_parse("1 + 2; 3;").ok(),
+        Some(vec![3, 3])
+    );
+}
+
+#[test]
+fn entry_points_parse_on_their_own() {
+    assert_eq!(
+        statements::ExpressionParser::lex_parse("1 + 2 + 3").ok(),
+        Some(6)
+    );
+    assert_eq!(statements::FactorParser::lex_parse("4").ok(), Some(4));
+}
+
+#[test]
+fn entry_points_reject_other_symbols() {
+    assert!(statements::ExpressionParser::lex_parse("1 + 2;").is_err());
+    assert!(statements::FactorParser::lex_parse("1 + 2").is_err());
+}
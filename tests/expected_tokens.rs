@@ -0,0 +1,38 @@
+mod common;
+
+use common::arithmetic::*;
+use semasia_parser::{ToDiagnostic, results::LexParseError};
+
+#[test]
+fn tokens_are_shown_by_display_name() {
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 + +") else {
+        panic!("should fail to parse");
+    };
+    let diagnostic = error.to_diagnostic();
+    assert_eq!(diagnostic.message, "unexpected token '+'");
+    assert_eq!(diagnostic.expected, ["a number", "'('"]);
+    assert_eq!(diagnostic.notes, ["expected tokens are a number, '('"]);
+    assert_eq!(TokenKind::Times.display_name(), "'*'");
+}
+
+#[test]
+fn expected_tokens_are_typed() {
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("(1 2") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.found(), Some(TokenKind::Number));
+    let expected = error.expected();
+    assert_eq!(
+        expected.iter().collect::<Vec<_>>(),
+        [TokenKind::Plus, TokenKind::Times, TokenKind::ClosedPar]
+    );
+    assert_eq!(expected.len(), 4);
+    assert_eq!(expected.to_string(), "'+', '*', ')', end of input");
+
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 +") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.found(), None);
+    assert!(error.expected().contains(TokenKind::OpenPar));
+    assert_eq!(error.expected().len(), 2);
+}
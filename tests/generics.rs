@@ -0,0 +1,37 @@
+use semasia::*;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod addition {
+    #[non_terminal]
+    #[start_symbol]
+    pub type E<T> = T;
+
+    #[regex(usize => r"\d+", f32 => (r"\d+(\.\d+)?", parse))]
+    pub type Num<T> = T;
+
+    #[token("+")]
+    pub struct Plus;
+
+    production!(P0<T>: E<T> -> (E<T>, Plus, Num<T>), |(e, _, num)| e + num);
+
+    production!(P1<T>: E<T> -> Num<T>);
+}
+
+#[test]
+fn instances_are_picked_by_type() {
+    assert_eq!(
+        addition::Parser::<usize>::lex_parse("10 + 3 + 9").ok(),
+        Some(22)
+    );
+    assert_eq!(
+        addition::Parser::<f32>::lex_parse("1.25 + 5.25").ok(),
+        Some(6.5)
+    );
+}
+
+#[test]
+fn instances_have_their_own_lexer() {
+    assert!(addition::Parser::<usize>::lex_parse("1.5 + 2").is_err());
+    assert_eq!(addition::Parser::<f32>::lex_parse("1 + 2").ok(), Some(3.0));
+}
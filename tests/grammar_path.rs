@@ -0,0 +1,15 @@
+use semasia::*;
+
+#[grammar(path = "grammars/sums.rs")]
+mod sums {}
+
+#[test]
+fn grammar_is_read_from_the_file() {
+    assert_eq!(sums::Parser::lex_parse("1 + 2 + 3").ok(), Some(6));
+    assert!(sums::Parser::lex_parse("1 +").is_err());
+}
+
+#[test]
+fn inner_attributes_of_the_file_apply_to_the_module() {
+    assert_eq!(sums::Parser::lex_parse(" 4\n+\t5 ").ok(), Some(9));
+}
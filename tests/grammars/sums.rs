@@ -0,0 +1,16 @@
+#![logos(skip r"\s+")]
+
+use super::*;
+
+#[start_symbol]
+#[non_terminal]
+pub type Sum = usize;
+
+#[regex(r"\d+", parse)]
+pub type Number = usize;
+
+#[token("+")]
+pub struct Plus;
+
+production!(Addition: Sum -> (Sum, Plus, Number), |(s, _, n)| s + n);
+production!(NoAddition: Sum -> Number);
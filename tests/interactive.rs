@@ -0,0 +1,39 @@
+mod common;
+
+use common::arithmetic::*;
+use semasia_parser::results::InputStatus;
+
+#[test]
+fn interactive_parse_tells_incomplete_from_invalid() {
+    assert!(matches!(
+        Parser::lex_parse_interactive("1 + 2"),
+        InputStatus::Complete(3)
+    ));
+    let InputStatus::Incomplete { expected } = Parser::lex_parse_interactive("(1 + 2") else {
+        panic!("should be incomplete");
+    };
+    assert_eq!(
+        expected.iter().collect::<Vec<_>>(),
+        [TokenKind::Plus, TokenKind::ClosedPar]
+    );
+    assert!(Parser::lex_parse_interactive("(1 + 2))").is_invalid());
+}
+
+#[test]
+fn check_doesnt_touch_the_parser() {
+    let mut parser = Parser::new();
+    assert!(parser.check_source("(1 + 2)").is_complete());
+    let InputStatus::Incomplete { expected } = parser.check_source("1 *") else {
+        panic!("should be incomplete");
+    };
+    assert!(expected.contains(TokenKind::Number));
+    assert!(matches!(parser.check_source("1 * * 2"), InputStatus::Invalid(span) if span == (4..5)));
+
+    let tokens = [Token::OpenPar(OpenPar), Token::Number(1)];
+    assert!(parser.check(tokens).is_incomplete());
+    assert!(matches!(
+        parser.check([Token::ClosedPar(ClosedPar)]),
+        InputStatus::Invalid(0)
+    ));
+    assert_eq!(parser.current_state(), Parser::new().current_state());
+}
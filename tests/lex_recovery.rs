@@ -0,0 +1,36 @@
+mod common;
+
+use common::arithmetic::*;
+use semasia_parser::{LexRecovery, results::Recovered};
+
+#[test]
+fn skip_invalid_reports_each_span() {
+    let Recovered { result, lex_errors } =
+        Parser::lex_parse_recovering("1 + ?? 2 $", LexRecovery::SkipInvalid);
+    assert_eq!(result.ok(), Some(3));
+    let spans = lex_errors
+        .iter()
+        .map(|error| error.span.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(spans, [4..5, 5..6, 9..10]);
+}
+
+#[test]
+fn resynchronize_merges_adjacent_spans() {
+    let Recovered { result, lex_errors } =
+        Parser::lex_parse_recovering("1 + ?? 2 $", LexRecovery::Resynchronize);
+    assert_eq!(result.ok(), Some(3));
+    let spans = lex_errors
+        .iter()
+        .map(|error| error.span.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(spans, [4..6, 9..10]);
+}
+
+#[test]
+fn parse_errors_are_still_reported() {
+    let Recovered { result, lex_errors } =
+        Parser::lex_parse_recovering("1 + ? +", LexRecovery::SkipInvalid);
+    assert!(result.is_err());
+    assert_eq!(lex_errors.len(), 1);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::arithmetic::*;
+use semasia_parser::{
+    LimitExceededReason, ParseLimits,
+    results::{LexParseError, ParseOneError, ParseTokenErrorReason},
+};
+
+fn limit_exceeded(limits: ParseLimits, source: &str) -> Option<LimitExceededReason> {
+    match Parser::new().with_limits(limits).do_lex_parse(source) {
+        Err(LexParseError::ParseError(error)) => match error.parse_one_error {
+            ParseOneError::LimitExceeded(limit_exceeded) => Some(limit_exceeded.reason()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[test]
+fn stack_depth_limit() {
+    let limits = ParseLimits::new().max_stack_depth(8);
+    let res = Parser::new().with_limits(limits).do_lex_parse("((1))");
+    assert_eq!(res.ok().map(|(res, _)| res), Some(1));
+    assert_eq!(
+        limit_exceeded(limits, "((((((((1))))))))"),
+        Some(LimitExceededReason::StackDepth(8))
+    );
+}
+
+#[test]
+fn stack_depth_counts_symbols() {
+    // `(1)` reaches three symbols on the stack, `((1))` four
+    let limits = ParseLimits::new().max_stack_depth(3);
+    let res = Parser::new().with_limits(limits).do_lex_parse("(1)");
+    assert_eq!(res.ok().map(|(res, _)| res), Some(1));
+    assert_eq!(
+        limit_exceeded(limits, "((1))"),
+        Some(LimitExceededReason::StackDepth(3))
+    );
+}
+
+#[test]
+fn stack_depth_is_checked_before_pushing() {
+    let mut parser = Parser::new().with_limits(ParseLimits::new().max_stack_depth(1));
+    assert!(parser.consume_token(Token::Number(1)).is_ok());
+    assert!(matches!(
+        parser.consume_token(Token::Plus(Plus)),
+        Err(ParseTokenErrorReason::LimitExceeded {
+            reason: LimitExceededReason::StackDepth(1),
+            ..
+        })
+    ));
+    assert_eq!(parser.consume_eof().ok(), Some(1));
+}
+
+#[test]
+fn actions_limit() {
+    let limits = ParseLimits::new().max_actions(10);
+    assert_eq!(
+        limit_exceeded(limits, "1 + 2 + 3"),
+        Some(LimitExceededReason::Actions(10))
+    );
+    let res = Parser::new().with_limits(limits).do_lex_parse("1");
+    assert_eq!(res.ok().map(|(res, _)| res), Some(1));
+}
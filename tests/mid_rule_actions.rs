@@ -0,0 +1,104 @@
+use semasia::*;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod blocks {
+    use super::*;
+
+    #[context]
+    #[derive(Default)]
+    pub struct Scopes {
+        pub depth: usize,
+        pub deepest: usize,
+    }
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Stmts = Vec<usize>;
+
+    #[non_terminal]
+    pub type Stmt = usize;
+
+    #[token("x")]
+    pub struct X;
+
+    #[token("{")]
+    pub struct LBrace;
+
+    #[token("}")]
+    pub struct RBrace;
+
+    production!(MoreStmts: Stmts -> (stmts: Stmts, stmt: Stmt), |mut stmts, stmt| {
+        stmts.push(stmt);
+        stmts
+    });
+    production!(NoStmts: Stmts -> (), |_| Vec::new());
+    production!(Leaf: Stmt -> (X, depth: @usize { ctx.depth }), |depth| depth);
+    production!(Block: Stmt -> (LBrace, @{ ctx.depth += 1; ctx.deepest = ctx.deepest.max(ctx.depth) }, stmts: Stmts, RBrace), |ctx, stmts| {
+        ctx.depth -= 1;
+        stmts.into_iter().sum()
+    });
+}
+
+#[test]
+fn actions_run_before_the_rest_of_the_body() {
+    let (depths, ctx) = blocks::Parser::default_ctx()
+        .do_lex_parse("x { x { x x } x } x")
+        .ok()
+        .unwrap();
+    assert_eq!(depths, vec![0, 1 + 2 + 2 + 1, 0]);
+    assert_eq!(ctx.depth, 0);
+    assert_eq!(ctx.deepest, 2);
+}
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod positional {
+    use super::*;
+
+    #[context]
+    #[derive(Default)]
+    pub struct Depth {
+        pub depth: usize,
+        pub deepest: usize,
+        pub groups: usize,
+    }
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Stmt = usize;
+
+    #[token("x")]
+    pub struct X;
+
+    #[token("{")]
+    pub struct LBrace;
+
+    #[token("}")]
+    pub struct RBrace;
+
+    #[token("(")]
+    pub struct LPar;
+
+    #[token(")")]
+    pub struct RPar;
+
+    production!(Leaf: Stmt -> X, |_| 0);
+    production!(Group: Stmt -> (LPar, @{ ctx.groups += 1 }, Stmt, RPar), |(_, _, s, _)| s);
+    production!(Block: Stmt -> (LBrace, @{ ctx.depth += 1; ctx.deepest = ctx.deepest.max(ctx.depth) }, Stmt, RBrace), |ctx, (_, _, s, _)| {
+        ctx.depth -= 1;
+        s + 1
+    });
+}
+
+#[test]
+fn actions_work_with_positional_closures() {
+    let (nesting, ctx) = positional::Parser::default_ctx()
+        .do_lex_parse("{ ({ { (x) } }) }")
+        .ok()
+        .unwrap();
+    assert_eq!(nesting, 3);
+    assert_eq!(ctx.depth, 0);
+    assert_eq!(ctx.deepest, 3);
+    assert_eq!(ctx.groups, 2);
+}
@@ -0,0 +1,129 @@
+use logos::Logos;
+use semasia::*;
+use semasia_parser::Kinded;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod language {
+    use super::*;
+
+    #[grammar]
+    #[logos(skip r"\s+")]
+    pub mod sums {
+        use super::*;
+
+        #[start_symbol]
+        #[non_terminal]
+        pub type Sum = usize;
+
+        #[non_terminal]
+        pub type Atom = usize;
+
+        #[regex(r"\d+", parse)]
+        pub type Number = usize;
+
+        #[token("+")]
+        pub struct Plus;
+
+        #[token("(")]
+        pub struct OpenPar;
+
+        #[token(")")]
+        pub struct ClosedPar;
+
+        production!(Addition: Sum -> (Sum, Plus, Atom), |(s, _, a)| s + a);
+        production!(NoAddition: Sum -> Atom);
+        production!(ActualNumber: Atom -> Number);
+        production!(Parenthesis: Atom -> (OpenPar, Sum, ClosedPar), |(_, s, _)| s);
+    }
+
+    #[grammar]
+    #[logos(skip r"\s+")]
+    pub mod negations {
+        use super::*;
+
+        #[start_symbol]
+        #[non_terminal]
+        pub type Negation = bool;
+
+        #[token("true")]
+        pub struct True;
+
+        #[token("!")]
+        pub struct Not;
+
+        #[token("(")]
+        pub struct OpenPar;
+
+        #[token(")")]
+        pub struct ClosedPar;
+
+        production!(Negated: Negation -> (Not, Negation), |(_, n)| !n);
+        production!(Truth: Negation -> True, |_| true);
+        production!(Parenthesis: Negation -> (OpenPar, Negation, ClosedPar), |(_, n, _)| n);
+    }
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Statement = String;
+
+    #[token("?")]
+    pub struct Question;
+
+    production!(SumStatement: Statement -> sums::Sum, |s| s.to_string());
+    production!(NegationStatement: Statement -> negations::Negation, |n| n.to_string());
+    production!(Wrapped: Statement -> (Question, negations::OpenPar, Statement, sums::ClosedPar), |(_, _, s, _)| s);
+}
+
+#[test]
+fn parent_parses_both_sub_grammars() {
+    assert_eq!(
+        language::Parser::lex_parse("1 + 2").ok(),
+        Some("3".to_string())
+    );
+    assert_eq!(
+        language::Parser::lex_parse("!!true").ok(),
+        Some("true".to_string())
+    );
+    assert_eq!(
+        language::Parser::lex_parse("! true").ok(),
+        Some("false".to_string())
+    );
+}
+
+#[test]
+fn sub_grammars_share_identical_tokens() {
+    assert_eq!(
+        language::Parser::lex_parse("((1) + (2 + 3))").ok(),
+        Some("6".to_string())
+    );
+    assert_eq!(
+        language::Parser::lex_parse("(!(true))").ok(),
+        Some("false".to_string())
+    );
+    assert_eq!(
+        language::Parser::lex_parse("?(?(!true))").ok(),
+        Some("false".to_string())
+    );
+}
+
+#[test]
+fn symbols_are_named_after_their_grammar() {
+    let mut lexer = language::Token::lexer("( true");
+    let open_par = lexer.next().unwrap().ok().unwrap();
+    assert_eq!(open_par.kind(), language::TokenKind::sums__OpenPar);
+    assert_eq!(format!("{open_par:?}"), "sums::OpenPar");
+    assert_eq!(open_par.to_string(), "'('");
+    let truth = lexer.next().unwrap().ok().unwrap();
+    assert_eq!(format!("{truth:?}"), "negations::True");
+}
+
+#[test]
+fn sub_grammars_are_standalone() {
+    assert_eq!(language::sums::Parser::lex_parse("(1+2)+3").ok(), Some(6));
+    assert_eq!(
+        language::negations::Parser::lex_parse("(!true)").ok(),
+        Some(false)
+    );
+    assert!(language::sums::Parser::lex_parse("!true").is_err());
+}
@@ -0,0 +1,34 @@
+use semasia::*;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod subtraction {
+    use super::*;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Expression = isize;
+
+    #[non_terminal]
+    pub type Term = isize;
+
+    #[regex(r"\d+", parse)]
+    pub type Number = isize;
+
+    #[token("-")]
+    pub struct Minus;
+
+    production!(Subtraction: Expression -> (lhs: Expression, Minus, rhs: Term), |rhs, lhs| lhs - rhs);
+    production!(NoSubtraction: Expression -> Term);
+    production!(Negation: Term -> (Minus, value: Number), |mut value| {
+        value = -value;
+        value
+    });
+    production!(ActualNumber: Term -> Number);
+}
+
+#[test]
+fn bindings_follow_names_not_order() {
+    assert_eq!(subtraction::Parser::lex_parse("10 - 3").ok(), Some(7));
+    assert_eq!(subtraction::Parser::lex_parse("10 - 3 - -2").ok(), Some(9));
+}
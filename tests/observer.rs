@@ -0,0 +1,70 @@
+mod common;
+
+use common::arithmetic::*;
+use semasia_parser::ParseObserver;
+
+#[derive(Default)]
+struct Events {
+    shifts: usize,
+    reductions: Vec<String>,
+    gotos: usize,
+    max_depth: usize,
+    errors: usize,
+    accepted: bool,
+}
+
+impl ParseObserver<NonTerminal, Token, ProductionName> for Events {
+    fn on_shift(&mut self, _token: &Token, _new_state: usize, depth: usize) {
+        self.shifts += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn on_reduce(&mut self, production: &ProductionName) {
+        self.reductions.push(format!("{production:?}"));
+    }
+
+    fn on_goto(&mut self, _non_terminal: &NonTerminal, _new_state: usize, depth: usize) {
+        self.gotos += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn on_error(&mut self, _state: usize) {
+        self.errors += 1;
+    }
+
+    fn on_accept(&mut self) {
+        self.accepted = true;
+    }
+}
+
+#[test]
+fn observer_sees_every_action() {
+    let mut events = Events::default();
+    let res = ObservedParser::with_ctx_and_observer((), &mut events).do_lex_parse("1 + 2");
+    assert_eq!(res.ok().map(|(res, _)| res), Some(3));
+    assert_eq!(events.shifts, 3);
+    assert_eq!(
+        events.reductions,
+        [
+            "ActualNumber",
+            "NoMultiplication",
+            "NoAddition",
+            "ActualNumber",
+            "NoMultiplication",
+            "Addition"
+        ]
+    );
+    assert_eq!(events.gotos, events.reductions.len());
+    assert_eq!(events.max_depth, 4);
+    assert_eq!(events.errors, 0);
+    assert!(events.accepted);
+}
+
+#[test]
+fn observer_sees_errors() {
+    let mut events = Events::default();
+    let res = ObservedParser::with_ctx_and_observer((), &mut events).do_lex_parse("1 + )");
+    assert!(res.is_err());
+    assert_eq!(events.errors, 1);
+    assert!(!events.accepted);
+}
@@ -0,0 +1,76 @@
+use semasia::*;
+
+#[derive(Default, Debug)]
+pub struct Mode {
+    types: bool,
+}
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod sections {
+    use super::*;
+
+    #[context]
+    use super::Mode;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Entries = Vec<String>;
+
+    #[non_terminal]
+    pub type Entry = Option<String>;
+
+    #[non_terminal]
+    pub type TypeName = String;
+
+    #[non_terminal]
+    pub type VarName = String;
+
+    #[regex(r"[a-z]+", to_string)]
+    pub type Ident = String;
+
+    #[token("types:")]
+    pub struct Types;
+
+    #[token("vars:")]
+    pub struct Vars;
+
+    production!(Empty: Entries -> (), |_| Vec::new());
+    production!(More: Entries -> (Entries, Entry), |(mut entries, entry)| { entries.extend(entry); entries });
+    production!(TypesMode: Entry -> Types, |ctx, _| { ctx.types = true; None });
+    production!(VarsMode: Entry -> Vars, |ctx, _| { ctx.types = false; None });
+    production!(TypeEntry: Entry -> TypeName, |name| Some(format!("type {name}")));
+    production!(VarEntry: Entry -> VarName, |name| Some(format!("var {name}")));
+
+    #[predicate(|ctx, _| ctx.types)]
+    production!(TypeRef: TypeName -> Ident, |name| name);
+    production!(VarRef: VarName -> Ident, |name| name);
+}
+
+#[test]
+fn predicate_picks_the_reduction() {
+    let (entries, mode) = sections::Parser::lex_parse_default_ctx("a types: b c vars: d types: e")
+        .ok()
+        .unwrap();
+    assert!(mode.types);
+    assert_eq!(
+        entries,
+        vec![
+            "var a".to_string(),
+            "type b".to_string(),
+            "type c".to_string(),
+            "var d".to_string(),
+            "type e".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn predicate_falls_back_to_the_other_action() {
+    assert_eq!(
+        sections::Parser::lex_parse_default_ctx("a b")
+            .ok()
+            .map(|(entries, _)| entries),
+        Some(vec!["var a".to_string(), "var b".to_string()])
+    );
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::arithmetic::*;
+
+#[test]
+fn prefix_stops_at_an_invalid_character() {
+    let (sum, rest) = Parser::lex_parse_prefix("1 + 2; rest").ok().unwrap();
+    assert_eq!(sum, 3);
+    assert_eq!(rest, 5..11);
+}
+
+#[test]
+fn prefix_stops_at_a_token_that_cannot_continue() {
+    let (product, rest) = Parser::lex_parse_prefix("(1 + 2) * 3 4 5").ok().unwrap();
+    assert_eq!(product, 9);
+    assert_eq!(rest, 12..15);
+}
+
+#[test]
+fn whole_input_leaves_an_empty_rest() {
+    let (sum, rest) = Parser::lex_parse_prefix("1 + 2").ok().unwrap();
+    assert_eq!(sum, 3);
+    assert_eq!(rest, 5..5);
+}
+
+#[test]
+fn prefix_has_to_be_accepted() {
+    assert!(Parser::lex_parse_prefix("1 +; rest").is_err());
+    assert!(Parser::lex_parse_prefix("(1 + 2 3").is_err());
+}
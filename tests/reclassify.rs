@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use semasia::*;
+
+#[derive(Default, Debug)]
+pub struct Typedefs {
+    names: HashSet<String>,
+}
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod c_like {
+    use super::*;
+
+    #[context]
+    #[reclassify(|ctx, token| match token {
+        Token::Ident(name) if ctx.names.contains(&name) => Token::TypeName(name),
+        token => token,
+    })]
+    use super::Typedefs;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Stmts = Vec<String>;
+
+    #[non_terminal]
+    pub type Stmt = String;
+
+    #[regex(r"[a-z]+", to_string)]
+    pub type Ident = String;
+
+    #[token]
+    #[display("a type name")]
+    pub type TypeName = String;
+
+    #[token("typedef")]
+    pub struct Typedef;
+
+    #[token(";")]
+    pub struct Semi;
+
+    production!(Empty: Stmts -> (), |_| Vec::new());
+    production!(More: Stmts -> (Stmts, Stmt), |(mut stmts, stmt)| { stmts.push(stmt); stmts });
+    production!(Define: Stmt -> (Typedef, Ident, Semi), |ctx, (_, name, _)| { ctx.names.insert(name.clone()); format!("typedef {name}") });
+    production!(Declare: Stmt -> (TypeName, Ident, Semi), |(ty, name, _)| format!("{name}: {ty}"));
+    production!(Use: Stmt -> (Ident, Semi), |(name, _)| format!("use {name}"));
+}
+
+#[test]
+fn tokens_are_reclassified_with_the_context() {
+    let (stmts, typedefs) = c_like::Parser::lex_parse_default_ctx("x; typedef t; t y; y;")
+        .ok()
+        .unwrap();
+    assert_eq!(stmts, vec!["use x", "typedef t", "y: t", "use y"]);
+    assert!(typedefs.names.contains("t"));
+}
+
+#[test]
+fn names_are_identifiers_before_their_typedef() {
+    assert!(c_like::Parser::lex_parse_default_ctx("t y; typedef t;").is_err());
+    assert!(c_like::Parser::lex_parse_default_ctx("typedef t; t;").is_err());
+}
+
+fn typedefs(names: &[&str]) -> Typedefs {
+    Typedefs {
+        names: names.iter().map(|name| name.to_string()).collect(),
+    }
+}
+
+#[test]
+fn checks_reclassify_tokens() {
+    let mut parser = c_like::Parser::with_ctx(typedefs(&["t"]));
+    assert!(parser.check_source("t y;").is_complete());
+    assert!(
+        parser
+            .check([
+                c_like::Token::Ident("t".to_string()),
+                c_like::Token::Ident("y".to_string()),
+                c_like::Token::Semi(c_like::Semi),
+            ])
+            .is_complete()
+    );
+    assert!(
+        c_like::Parser::default_ctx()
+            .check_source("t y;")
+            .is_invalid()
+    );
+}
+
+#[test]
+fn completions_reclassify_tokens() {
+    let completions = c_like::Parser::with_ctx(typedefs(&["t"]))
+        .completions_at("t ", 2)
+        .unwrap();
+    assert!(completions.tokens.contains(c_like::TokenKind::Ident));
+    let completions = c_like::Parser::default_ctx()
+        .completions_at("t ", 2)
+        .unwrap();
+    assert!(!completions.tokens.contains(c_like::TokenKind::Ident));
+}
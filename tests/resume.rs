@@ -0,0 +1,72 @@
+mod common;
+
+use std::iter::once;
+
+use common::arithmetic::*;
+use semasia_parser::{ToDiagnostic, results::LexParseError};
+
+#[test]
+fn resume_after_end_of_input() {
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("(1") else {
+        panic!("should fail to parse");
+    };
+    assert!(error.expected().contains(TokenKind::ClosedPar));
+    assert!(!error.expected().contains_end_of_input());
+    assert_eq!(error.resume_lex("* 2)").ok().map(|(res, _)| res), Some(2));
+
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("(1 + 2") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.resume_lex("* 3)").ok().map(|(res, _)| res), Some(7));
+}
+
+#[test]
+fn resume_drops_the_leftover_token() {
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 + + 2") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.found(), Some(TokenKind::Plus));
+    assert_eq!(error.resume_lex("2").ok().map(|(res, _)| res), Some(3));
+
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 + + 2") else {
+        panic!("should fail to parse");
+    };
+    let res = error.resume_with(|leftover| {
+        once(Token::Number(7))
+            .chain(leftover)
+            .chain(once(Token::Number(2)))
+    });
+    assert_eq!(res.ok().map(|(res, _)| res), Some(10));
+}
+
+#[test]
+fn spans_after_a_resume_are_in_the_whole_input() {
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("(1 + 2") else {
+        panic!("should fail to parse");
+    };
+    // the rest starts where the source ended
+    let Err(LexParseError::ParseError(error)) = error.resume_lex(" 3)") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.parse_one_error.span(), Some(&(7..8)));
+    assert_eq!(error.to_diagnostic().span, 1..2);
+
+    let Err(LexParseError::ParseError(error)) = Parser::lex_parse("1 + + 2") else {
+        panic!("should fail to parse");
+    };
+    // and in place of the rejected token
+    let Err(LexParseError::ParseError(error)) = error.resume_lex("2 3") else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.parse_one_error.span(), Some(&(6..7)));
+
+    let Err(error) =
+        Parser::parse([Token::Number(1), Token::Plus(Plus), Token::Plus(Plus)].into_iter())
+    else {
+        panic!("should fail to parse");
+    };
+    let Err(error) = error.resume([Token::Number(2), Token::Number(3)]) else {
+        panic!("should fail to parse");
+    };
+    assert_eq!(error.parse_one_error.span(), Some(&3));
+}
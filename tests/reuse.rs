@@ -0,0 +1,49 @@
+mod common;
+
+use common::arithmetic;
+use semasia::*;
+
+#[grammar]
+#[logos(skip r"\s+")]
+mod totals {
+    use super::*;
+
+    #[context]
+    pub type Totals = Vec<usize>;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Sum = usize;
+
+    #[regex(r"\d+", parse)]
+    pub type Number = usize;
+
+    #[token("+")]
+    pub struct Plus;
+
+    production!(More: Sum -> (Sum, Plus, Number), |(sum, _, number)| sum + number);
+    production!(First: Sum -> Number, |totals, number| {
+        totals.push(number);
+        number
+    });
+}
+
+#[test]
+fn parser_is_reset_between_parses() {
+    let mut parser = arithmetic::Parser::new().with_capacity(64);
+    assert_eq!(parser.do_lex_parse_mut("1 + 2").ok(), Some(3));
+    assert!(parser.do_lex_parse_mut("(1 +").is_err());
+    assert_eq!(parser.do_lex_parse_mut("4 * 5").ok(), Some(20));
+    assert_eq!(parser.actions(), 9);
+}
+
+#[test]
+fn context_is_kept_between_parses() {
+    let mut parser = totals::Parser::with_ctx(Vec::new());
+    assert_eq!(parser.do_lex_parse_mut("1 + 2").ok(), Some(3));
+    assert_eq!(parser.do_lex_parse_mut("4").ok(), Some(4));
+    assert_eq!(parser.ctx(), &[1, 4]);
+    assert_eq!(parser.replace_ctx(Vec::new()), [1, 4]);
+    assert_eq!(parser.do_lex_parse_mut("7").ok(), Some(7));
+    assert_eq!(parser.ctx(), &[7]);
+}
@@ -0,0 +1,133 @@
+use semasia::*;
+use semasia_parser::RollbackError;
+
+#[grammar]
+#[derive(Clone)]
+#[non_terminal_derive(Clone)]
+#[logos(skip r"\s+")]
+mod arithmetic {
+    use super::*;
+
+    #[start_symbol]
+    #[non_terminal]
+    pub type Expression = usize;
+
+    #[non_terminal]
+    pub type Term = usize;
+
+    #[regex(r"\d+", parse)]
+    pub type Number = usize;
+
+    #[token("+")]
+    #[derive(Clone)]
+    pub struct Plus;
+
+    #[token("*")]
+    #[derive(Clone)]
+    pub struct Times;
+
+    production!(Addition: Expression -> (Expression, Plus, Term), |(e, _, t)| e + t);
+    production!(NoAddition: Expression -> Term);
+    production!(Multiplication: Term -> (Term, Times, Number), |(t, _, n)| t * n);
+    production!(NoMultiplication: Term -> Number);
+}
+
+use arithmetic::*;
+
+fn consume(parser: &mut Parser, tokens: impl IntoIterator<Item = Token>) -> bool {
+    tokens
+        .into_iter()
+        .all(|token| parser.consume_token(token).is_ok())
+}
+
+#[test]
+fn rollback_to_checkpoint_undoes_reductions() {
+    let mut parser = Parser::new();
+    assert!(consume(
+        &mut parser,
+        [Token::Number(1), Token::Plus(Plus), Token::Number(2)]
+    ));
+    let checkpoint = parser.checkpoint();
+    // `+` reduces `1 + 2` to an expression
+    assert!(consume(&mut parser, [Token::Plus(Plus)]));
+    assert!(!consume(&mut parser, [Token::Times(Times)]));
+    parser.rollback(checkpoint);
+    assert!(consume(
+        &mut parser,
+        [Token::Times(Times), Token::Number(3)]
+    ));
+    assert_eq!(parser.consume_eof().ok(), Some(7));
+}
+
+#[test]
+fn rollback_to_mark_drops_shifted_tokens() {
+    let mut parser = Parser::new();
+    assert!(consume(&mut parser, [Token::Number(1), Token::Plus(Plus)]));
+    let mark = parser.mark();
+    assert!(consume(&mut parser, [Token::Number(2)]));
+    assert_eq!(parser.rollback_to_mark(mark), Ok(()));
+    assert!(consume(&mut parser, [Token::Number(3)]));
+    assert_eq!(parser.consume_eof().ok(), Some(4));
+}
+
+#[test]
+fn rollback_to_mark_restores_reduced_symbols() {
+    let mut parser = Parser::new();
+    assert!(consume(
+        &mut parser,
+        [Token::Number(1), Token::Plus(Plus), Token::Number(2)]
+    ));
+    let mark = parser.mark();
+    // `+` reduces `1 + 2`, consuming the symbols below the mark
+    assert!(consume(&mut parser, [Token::Plus(Plus)]));
+    assert_eq!(parser.rollback_to_mark(mark), Ok(()));
+    assert!(consume(
+        &mut parser,
+        [Token::Times(Times), Token::Number(3)]
+    ));
+    assert_eq!(parser.consume_eof().ok(), Some(7));
+}
+
+#[test]
+fn releasing_a_mark_releases_the_later_ones() {
+    let mut parser = Parser::new();
+    assert!(consume(&mut parser, [Token::Number(1)]));
+    let outer = parser.mark();
+    assert!(consume(&mut parser, [Token::Plus(Plus)]));
+    let inner = parser.mark();
+    parser.release_mark(outer);
+    assert_eq!(
+        parser.rollback_to_mark(inner),
+        Err(RollbackError::MarkReleased)
+    );
+}
+
+#[test]
+fn marks_taken_after_a_checkpoint_are_released_by_its_rollback() {
+    let mut parser = Parser::new();
+    assert!(consume(&mut parser, [Token::Number(1)]));
+    let checkpoint = parser.checkpoint();
+    let stale = parser.mark();
+    parser.rollback(checkpoint);
+    let _fresh = parser.mark();
+    assert_eq!(
+        parser.rollback_to_mark(stale),
+        Err(RollbackError::MarkReleased)
+    );
+}
+
+#[test]
+fn checkpoints_keep_the_marks_taken_before_them() {
+    let mut parser = Parser::new();
+    assert!(consume(&mut parser, [Token::Number(1), Token::Plus(Plus)]));
+    let mark = parser.mark();
+    assert!(consume(&mut parser, [Token::Number(2)]));
+    let checkpoint = parser.checkpoint();
+    assert!(consume(&mut parser, [Token::Plus(Plus)]));
+    parser.rollback(checkpoint);
+    // the reduction undone by the checkpoint is replayed with the mark still alive
+    assert!(consume(&mut parser, [Token::Plus(Plus)]));
+    assert_eq!(parser.rollback_to_mark(mark), Ok(()));
+    assert!(consume(&mut parser, [Token::Number(3)]));
+    assert_eq!(parser.consume_eof().ok(), Some(4));
+}